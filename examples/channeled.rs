@@ -0,0 +1,30 @@
+use windows_hotkeys::{
+    channeled::HotkeyThread,
+    keys::{ModKey, VKey},
+    HotkeyManagerImpl,
+};
+
+fn main() {
+    // Spawn a background thread that registers the hotkeys and handles the hotkey events. The
+    // setup closure runs on the background thread, since windows delivers the hotkey events to
+    // the thread that registered the hotkeys
+    let hotkeys = HotkeyThread::spawn(|hkm| {
+        hkm.register(VKey::A, &[ModKey::Alt], || "ALT + A")?;
+        hkm.register(VKey::B, &[ModKey::Alt], || "ALT + B")?;
+        hkm.register(VKey::Escape, &[ModKey::Alt], || "quit")?;
+        Ok(())
+    })
+    .unwrap();
+
+    // The return values of the callbacks are received on this thread, so they can be handled in
+    // an existing main loop
+    while let Ok(name) = hotkeys.recv() {
+        if name == "quit" {
+            break;
+        }
+        println!("Hotkey {} was pressed", name);
+    }
+
+    // Stop the background thread, which also unregisters the hotkeys
+    hotkeys.shutdown().unwrap();
+}
@@ -40,7 +40,7 @@ fn main() {
     loop {
         // Handle one hotkey event. This will block until a hotkey event is triggered and return
         // the return value of the callback
-        let control_flow = hkm.handle_hotkey();
+        let control_flow = hkm.handle_hotkey().unwrap();
 
         // Since the callbacks return a `ControlFlow` variant, check if the loop should exit
         match control_flow {
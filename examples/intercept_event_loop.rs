@@ -29,7 +29,7 @@ fn main() {
 
     // Run the event handler in a blocking loop. This will block until interrupted and execute the 
     // set callbacks when registered hotkeys are detected
-    hkm.event_loop();
+    hkm.event_loop().unwrap();
 
     println!("Event Loop interrupted");
 }
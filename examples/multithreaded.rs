@@ -50,7 +50,7 @@ fn main() {
                 );
 
                 // Start listening on the new thread with HKM2
-                hkm2.event_loop();
+                hkm2.event_loop().unwrap();
 
                 println!("HotkeyManager2 ended");
             } else {
@@ -87,7 +87,7 @@ fn main() {
 
         // Start EventLoop for HKM2 on a different thread than the one that was used for registering
         // hotkeys. This doesn't work normally
-        hkm.event_loop();
+        hkm.event_loop().unwrap();
     })
     .join()
     .unwrap();
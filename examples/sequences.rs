@@ -0,0 +1,28 @@
+use windows_hotkeys::{
+    keys::{KeyCombination, ModKey, VKey},
+    sequences::SequenceManager,
+};
+
+fn main() {
+    let mut sm = SequenceManager::new();
+
+    let ctrl_k = KeyCombination::new(VKey::K, &[ModKey::Ctrl]);
+    let ctrl_d = KeyCombination::new(VKey::D, &[ModKey::Ctrl]);
+    let ctrl_c = KeyCombination::new(VKey::C, &[ModKey::Ctrl]);
+
+    // Both sequences start with CTRL + K, the second step decides which one is executed
+    sm.register(&[ctrl_k.clone(), ctrl_d], || "CTRL + K, CTRL + D")
+        .unwrap();
+    sm.register(&[ctrl_k, ctrl_c], || "CTRL + K, CTRL + C")
+        .unwrap();
+
+    let quit = "ALT + ESCAPE".parse::<KeyCombination>().unwrap();
+    sm.register(&[quit], || "quit").unwrap();
+
+    while let Ok(Some(name)) = sm.handle_sequence() {
+        if name == "quit" {
+            break;
+        }
+        println!("Sequence {} was pressed", name);
+    }
+}
@@ -30,5 +30,5 @@ fn main() {
 
     // Run the event handler in a blocking loop. This will block forever and execute the set
     // callbacks when registered hotkeys are detected
-    hkm.event_loop();
+    hkm.event_loop().unwrap();
 }
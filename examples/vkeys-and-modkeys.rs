@@ -75,5 +75,5 @@ fn main() {
     })
     .unwrap();
 
-    hkm.event_loop();
+    hkm.event_loop().unwrap();
 }
@@ -0,0 +1,164 @@
+//! Export the registered hotkeys as accelerator table entries, so menus can show the same
+//! shortcuts as the global hotkeys.
+//!
+//! An `ACCEL` entry only consists of the main key, the CTRL, SHIFT and ALT modifiers and a
+//! command id. The extra keys and all `RegisterOptions` of a hotkey, like conditions, can't be
+//! represented and are dropped. Hotkeys that use the WIN modifier or whose id doesn't fit into
+//! the 16 bit command id are skipped entirely.
+//!
+
+use crate::{
+    keys::{ModKey, VKey},
+    HotkeyId,
+};
+
+/// The key is a virtual keycode instead of a character code
+pub const FVIRTKEY: u8 = 0x01;
+/// The SHIFT key must be held down
+pub const FSHIFT: u8 = 0x04;
+/// The CTRL key must be held down
+pub const FCONTROL: u8 = 0x08;
+/// The ALT key must be held down
+pub const FALT: u8 = 0x10;
+
+/// An accelerator table entry with the same layout as the windows `ACCEL` structure. It can be
+/// converted into the `ACCEL` type of `winapi` or `windows-sys` and used with
+/// `CreateAcceleratorTableW`.
+///
+/// See: <https://learn.microsoft.com/en-us/windows/win32/api/winuser/ns-winuser-accel>
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(C)]
+pub struct Accel {
+    /// The accelerator flags, `FVIRTKEY` combined with the modifier flags
+    pub flags: u8,
+    /// The virtual keycode of the main key
+    pub key: u16,
+    /// The command id, which is the raw id of the hotkey
+    pub cmd: u16,
+}
+
+impl Accel {
+    /// Create the accelerator table entry for a hotkey. Returns `None` if the hotkey can't be
+    /// represented as an accelerator, see the module documentation.
+    ///
+    pub fn from_hotkey(id: HotkeyId, key: VKey, key_modifiers: &[ModKey]) -> Option<Accel> {
+        let mut flags = FVIRTKEY;
+        for modifier in key_modifiers {
+            flags |= match modifier {
+                ModKey::Shift => FSHIFT,
+                ModKey::Ctrl => FCONTROL,
+                ModKey::Alt => FALT,
+                ModKey::Win => return None,
+            };
+        }
+
+        Some(Accel {
+            flags,
+            key: u16::try_from(key.to_vk_code()).ok()?,
+            cmd: u16::try_from(id.as_i32()).ok()?,
+        })
+    }
+
+    /// Get the id of the hotkey that the accelerator was created for.
+    ///
+    pub const fn hotkey_id(&self) -> HotkeyId {
+        HotkeyId::from_raw(self.cmd as i32)
+    }
+}
+
+#[cfg(windows)]
+impl From<Accel> for winapi::um::winuser::ACCEL {
+    fn from(accel: Accel) -> Self {
+        winapi::um::winuser::ACCEL {
+            fVirt: accel.flags,
+            key: accel.key,
+            cmd: accel.cmd,
+        }
+    }
+}
+
+#[cfg(all(windows, feature = "windows-sys"))]
+impl From<Accel> for windows_sys::Win32::UI::WindowsAndMessaging::ACCEL {
+    fn from(accel: Accel) -> Self {
+        windows_sys::Win32::UI::WindowsAndMessaging::ACCEL {
+            fVirt: accel.flags,
+            key: accel.key,
+            cmd: accel.cmd,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{backend::mock::MockBackend, singlethreaded, HotkeyManagerImpl};
+
+    #[test]
+    fn modifiers_are_mapped_to_flags() {
+        let accel = Accel::from_hotkey(HotkeyId(7), VKey::A, &[]).unwrap();
+        assert_eq!(accel.flags, FVIRTKEY);
+        assert_eq!(accel.key, 0x41);
+        assert_eq!(accel.cmd, 7);
+
+        let accel = Accel::from_hotkey(
+            HotkeyId(7),
+            VKey::A,
+            &[ModKey::Ctrl, ModKey::Shift, ModKey::Alt],
+        )
+        .unwrap();
+        assert_eq!(accel.flags, FVIRTKEY | FCONTROL | FSHIFT | FALT);
+        assert_eq!(accel.hotkey_id(), HotkeyId(7));
+    }
+
+    #[test]
+    fn win_modifier_is_skipped() {
+        assert_eq!(
+            Accel::from_hotkey(HotkeyId(1), VKey::A, &[ModKey::Ctrl, ModKey::Win]),
+            None
+        );
+    }
+
+    #[test]
+    fn ids_outside_of_u16_are_skipped() {
+        assert!(Accel::from_hotkey(HotkeyId(0xFFFF), VKey::A, &[]).is_some());
+        assert_eq!(Accel::from_hotkey(HotkeyId(0x1_0000), VKey::A, &[]), None);
+        assert_eq!(Accel::from_hotkey(HotkeyId(-1), VKey::A, &[]), None);
+        assert_eq!(
+            Accel::from_hotkey(HotkeyId(1), VKey::CustomKeyCode(-1), &[]),
+            None
+        );
+    }
+
+    #[test]
+    fn layout_matches_accel() {
+        // `BYTE fVirt; WORD key; WORD cmd;`
+        assert_eq!(std::mem::size_of::<Accel>(), 6);
+        assert_eq!(std::mem::align_of::<Accel>(), 2);
+
+        #[cfg(windows)]
+        assert_eq!(
+            std::mem::size_of::<Accel>(),
+            std::mem::size_of::<winapi::um::winuser::ACCEL>()
+        );
+    }
+
+    #[test]
+    fn accel_table_skips_unrepresentable_hotkeys() {
+        let mut hkm = singlethreaded::HotkeyManager::<()>::with_backend(MockBackend::new());
+        let ctrl_a = hkm.register(VKey::A, &[ModKey::Ctrl], || ()).unwrap();
+        hkm.register(VKey::B, &[ModKey::Win], || ()).unwrap();
+        let alt_c = hkm.register(VKey::C, &[ModKey::Alt], || ()).unwrap();
+
+        let table = hkm.to_accel_table();
+
+        assert_eq!(
+            table,
+            vec![
+                Accel::from_hotkey(ctrl_a, VKey::A, &[ModKey::Ctrl]).unwrap(),
+                Accel::from_hotkey(alt_c, VKey::C, &[ModKey::Alt]).unwrap(),
+            ]
+        );
+        assert_eq!(table[1].flags, FVIRTKEY | FALT);
+    }
+}
@@ -0,0 +1,283 @@
+//! The `HotkeyBackend` trait abstracts the operating system calls that are used by the
+//! singlethreaded `HotkeyManager`. The `Win32Backend` is used by default on windows. Custom
+//! backends can be used with `HotkeyManager::with_backend`, for example the `StubBackend` to test
+//! the hotkey handling logic with synthetic hotkey events.
+//!
+//! With the `windows-sys` feature, the `Win32Backend` uses the `windows-sys` bindings instead of
+//! `winapi`. Both implementations behave the same.
+//!
+
+use std::time::Duration;
+
+use crate::{
+    error::HkError,
+    keys::{ModKey, VKey},
+    HotkeyId, InterruptHandle, LoopQuitter,
+};
+
+#[cfg(all(windows, feature = "hook"))]
+mod keyboard_hook;
+#[cfg(test)]
+pub(crate) mod mock;
+#[cfg(all(windows, feature = "mouse"))]
+mod mouse_hook;
+mod stub;
+#[cfg(all(windows, not(feature = "windows-sys")))]
+mod win32;
+#[cfg(all(windows, feature = "windows-sys"))]
+mod win32_sys;
+
+pub use stub::StubBackend;
+#[cfg(all(windows, not(feature = "windows-sys")))]
+pub use win32::Win32Backend;
+#[cfg(all(windows, feature = "windows-sys"))]
+pub use win32_sys::Win32Backend;
+
+/// A message that was taken from the message queue of a `HotkeyBackend`
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BackendMessage {
+    /// The hotkey with the given id was triggered. Contains the key combination that triggered
+    /// it, if the backend reports one
+    Hotkey(HotkeyId, Option<HotkeyTrigger>),
+    /// The event loop was interrupted through an `InterruptHandle`
+    Interrupt,
+    /// The event loop was stopped through a `LoopQuitter`
+    Quit,
+    /// Any other message, which is ignored
+    Other,
+}
+
+/// The key combination that triggered a hotkey event, as reported in the `lParam` of the
+/// `WM_HOTKEY` message, together with the time and cursor position of the message.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HotkeyTrigger {
+    key: VKey,
+    /// The combined modifier codes, see `ModKey::combine`
+    modifiers: u32,
+    /// The `time` of the message
+    time: Option<u32>,
+    /// The `pt` of the message
+    cursor_pos: Option<(i32, i32)>,
+}
+
+impl HotkeyTrigger {
+    /// Create a `HotkeyTrigger` for the given key combination.
+    ///
+    pub fn new(key: VKey, key_modifiers: &[ModKey]) -> HotkeyTrigger {
+        HotkeyTrigger {
+            key,
+            modifiers: ModKey::combine(key_modifiers),
+            time: None,
+            cursor_pos: None,
+        }
+    }
+
+    /// Decode the `lParam` of a `WM_HOTKEY` message. The low word contains the modifier codes
+    /// and the high word contains the virtual keycode of the main key.
+    ///
+    pub fn from_lparam(lparam: isize) -> HotkeyTrigger {
+        HotkeyTrigger {
+            key: VKey::CustomKeyCode(((lparam >> 16) & 0xFFFF) as i32),
+            modifiers: (lparam & 0xFFFF) as u32 & ModKey::combine(&ModKey::all()),
+            time: None,
+            cursor_pos: None,
+        }
+    }
+
+    /// Set the `time` and `pt` of the message that reported the hotkey event.
+    ///
+    pub fn with_message_info(mut self, time: u32, cursor_pos: (i32, i32)) -> HotkeyTrigger {
+        self.time = Some(time);
+        self.cursor_pos = Some(cursor_pos);
+        self
+    }
+
+    /// Encode the key combination as the `lParam` of a `WM_HOTKEY` message. This is the inverse
+    /// of `from_lparam`.
+    ///
+    pub fn to_lparam(&self) -> isize {
+        ((self.key.to_vk_code() as isize & 0xFFFF) << 16) | self.modifiers as isize
+    }
+
+    /// Get the main key that triggered the hotkey.
+    ///
+    pub fn key(&self) -> VKey {
+        self.key
+    }
+
+    /// Get the modifier keys that were held down when the hotkey was triggered.
+    ///
+    pub fn modifiers(&self) -> Vec<ModKey> {
+        ModKey::from_bits(self.modifiers)
+    }
+
+    /// Get the time of the message in milliseconds since the system was started. Returns `None`
+    /// if the time was not reported.
+    ///
+    pub fn time(&self) -> Option<u32> {
+        self.time
+    }
+
+    /// Get the cursor position in screen coordinates when the message was posted. Returns `None`
+    /// if the position was not reported.
+    ///
+    pub fn cursor_pos(&self) -> Option<(i32, i32)> {
+        self.cursor_pos
+    }
+
+    /// Check if exactly the given modifier keys were held down. The order doesn't matter.
+    ///
+    pub fn matches_modifiers(&self, key_modifiers: &[ModKey]) -> bool {
+        self.modifiers == ModKey::combine(key_modifiers)
+    }
+}
+
+/// The operating system calls that are used by the singlethreaded `HotkeyManager` to register
+/// hotkeys and receive the hotkey events.
+///
+pub trait HotkeyBackend {
+    /// Register the hotkey combination using the given id. Unless `allow_repeat` is set, the
+    /// hotkey must not be triggered repeatedly while the keys are held down.
+    ///
+    fn register_hotkey(
+        &self,
+        id: HotkeyId,
+        key: VKey,
+        key_modifiers: &[ModKey],
+        allow_repeat: bool,
+    ) -> Result<(), HkError>;
+
+    /// Same as `register_hotkey`, but the key events of the hotkey must not be passed on to other
+    /// applications. Returns `HkError::Unsupported` by default.
+    ///
+    #[cfg(feature = "hook")]
+    fn register_suppressing_hotkey(
+        &self,
+        id: HotkeyId,
+        key: VKey,
+        key_modifiers: &[ModKey],
+        allow_repeat: bool,
+    ) -> Result<(), HkError> {
+        let _ = (id, key, key_modifiers, allow_repeat);
+        Err(HkError::Unsupported)
+    }
+
+    /// Unregister the hotkey with the given id.
+    ///
+    fn unregister_hotkey(&self, id: HotkeyId) -> Result<(), HkError>;
+
+    /// Put a hotkey event for the given id into the message queue, as if the hotkey was pressed.
+    /// The trigger is reported with the event like the `lParam` of a `WM_HOTKEY` message. Returns
+    /// `HkError::Unsupported` by default.
+    ///
+    fn post_hotkey(&self, id: HotkeyId, trigger: HotkeyTrigger) -> Result<(), HkError> {
+        let _ = (id, trigger);
+        Err(HkError::Unsupported)
+    }
+
+    /// Block until the next message arrives and take it from the message queue.
+    ///
+    fn get_message(&self) -> Result<BackendMessage, HkError>;
+
+    /// Take the next message from the message queue without blocking. If `hotkeys_only` is set,
+    /// only hotkey messages are taken from the queue. Returns `None` if there is no message.
+    ///
+    fn peek_message(&self, hotkeys_only: bool) -> Option<BackendMessage>;
+
+    /// Block until a new message arrives or the timeout runs out. Returns false if the timeout
+    /// ran out.
+    ///
+    fn wait_message(&self, timeout: Duration) -> Result<bool, HkError>;
+
+    /// Check if the given key is currently pressed.
+    ///
+    fn key_state(&self, key: VKey) -> bool;
+
+    /// Get an `InterruptHandle` that makes `get_message` return `BackendMessage::Interrupt`.
+    ///
+    fn interrupt_handle(&self) -> InterruptHandle;
+
+    /// Get a `LoopQuitter` that makes `get_message` return `BackendMessage::Quit`.
+    ///
+    fn quit_handle(&self) -> LoopQuitter;
+
+    /// Get the id of the thread that the hotkeys are registered for and that has to receive the
+    /// hotkey events. Returns `None` by default, for backends that work on any thread.
+    ///
+    fn owner_thread_id(&self) -> Option<u32> {
+        None
+    }
+}
+
+/// Get the backend that is used by `HotkeyManager::new`. This is the `Win32Backend` on windows
+/// and an unsupported `StubBackend` on other platforms.
+///
+pub(crate) fn default_backend() -> Box<dyn HotkeyBackend> {
+    #[cfg(windows)]
+    return Box::new(Win32Backend::new());
+
+    #[cfg(not(windows))]
+    return Box::new(StubBackend::unsupported());
+}
+
+/// Get the id of the current thread. On other platforms than windows, the id is only unique
+/// within the process and is not an operating system thread id.
+///
+pub(crate) fn current_thread_id() -> Option<u32> {
+    #[cfg(all(windows, not(feature = "windows-sys")))]
+    return Some(unsafe { winapi::um::processthreadsapi::GetCurrentThreadId() });
+
+    #[cfg(all(windows, feature = "windows-sys"))]
+    return Some(unsafe { windows_sys::Win32::System::Threading::GetCurrentThreadId() });
+
+    #[cfg(not(windows))]
+    {
+        use std::sync::atomic::{AtomicU32, Ordering};
+
+        static NEXT_THREAD_ID: AtomicU32 = AtomicU32::new(1);
+        thread_local! {
+            static THREAD_ID: u32 = NEXT_THREAD_ID.fetch_add(1, Ordering::Relaxed);
+        }
+
+        Some(THREAD_ID.with(|id| *id))
+    }
+}
+
+/// Get the combined modifier codes of the modifier keys that are currently pressed. Used by the
+/// low-level hooks, which don't get the modifiers with the key events.
+///
+#[cfg(all(windows, any(feature = "mouse", feature = "hook")))]
+fn current_modifiers() -> u32 {
+    use crate::get_global_keystate;
+
+    let mut modifiers = Vec::new();
+    if get_global_keystate(VKey::Control) {
+        modifiers.push(ModKey::Ctrl);
+    }
+    if get_global_keystate(VKey::Menu) {
+        modifiers.push(ModKey::Alt);
+    }
+    if get_global_keystate(VKey::Shift) {
+        modifiers.push(ModKey::Shift);
+    }
+    if get_global_keystate(VKey::LWin) || get_global_keystate(VKey::RWin) {
+        modifiers.push(ModKey::Win);
+    }
+
+    ModKey::combine(&modifiers)
+}
+
+/// Convert a `Duration` into a timeout in milliseconds for the windows wait functions. Partial
+/// milliseconds are rounded up, so the wait never ends before the duration has passed. Durations
+/// that saturate `u32` are converted to `INFINITE`.
+///
+#[cfg(windows)]
+fn timeout_to_ms(timeout: Duration) -> u32 {
+    /// Timeout value of the windows wait functions that never times out
+    const INFINITE: u32 = 0xFFFFFFFF;
+
+    let ms = timeout.as_nanos().div_ceil(1_000_000);
+    u32::try_from(ms).unwrap_or(INFINITE)
+}
@@ -0,0 +1,242 @@
+use std::cell::RefCell;
+
+use winapi::shared::minwindef::{LPARAM, LRESULT, WPARAM};
+use winapi::shared::windef::{HHOOK, HWND};
+use winapi::um::errhandlingapi::GetLastError;
+use winapi::um::libloaderapi::GetModuleHandleW;
+use winapi::um::winuser::{
+    CallNextHookEx, PostMessageW, PostThreadMessageW, SetWindowsHookExW, UnhookWindowsHookEx,
+    HC_ACTION, KBDLLHOOKSTRUCT, WH_KEYBOARD_LL, WM_HOTKEY, WM_KEYDOWN, WM_KEYUP, WM_SYSKEYDOWN,
+    WM_SYSKEYUP,
+};
+
+use crate::{
+    backend::{current_modifiers, HotkeyTrigger},
+    error::HkError,
+    keys::{ModKey, VKey},
+    HotkeyId,
+};
+
+/// A suppressing hotkey of a `Win32Backend`
+///
+struct KeyBinding {
+    /// The window of the backend that receives the hotkey events, or 0 for the thread queue
+    hwnd: usize,
+    /// The thread of the backend, used if there is no window
+    thread_id: u32,
+    id: HotkeyId,
+    /// The virtual keycode of the main key
+    vk_code: u32,
+    /// The combined modifier codes, see `ModKey::combine`
+    modifiers: u32,
+    allow_repeat: bool,
+}
+
+/// The installed keyboard hook of the current thread and the suppressing hotkeys of all backends
+/// on this thread
+///
+struct KeyboardHook {
+    hook: HHOOK,
+    bindings: Vec<KeyBinding>,
+    /// The keycodes of the main keys that were suppressed and are still held down. Their key up
+    /// events are suppressed as well, and their auto-repeat triggers the hotkey again only if
+    /// allowed
+    held: Vec<u32>,
+}
+
+thread_local! {
+    /// Low-level hooks are called on the thread that installed them and don't get any user data,
+    /// so the hotkeys are stored per thread
+    static KEYBOARD_HOOK: RefCell<Option<KeyboardHook>> = const { RefCell::new(None) };
+}
+
+/// Register a suppressing hotkey for the backend with the given window and thread. The keyboard
+/// hook is installed for the first suppressing hotkey of the thread.
+///
+pub(crate) fn register(
+    hwnd: usize,
+    thread_id: u32,
+    id: HotkeyId,
+    key: VKey,
+    key_modifiers: &[ModKey],
+    allow_repeat: bool,
+) -> Result<(), HkError> {
+    let vk_code = key.to_vk_code() as u32;
+    let modifiers = ModKey::combine(key_modifiers);
+
+    KEYBOARD_HOOK.with(|keyboard_hook| {
+        let mut keyboard_hook = keyboard_hook.borrow_mut();
+
+        // Behave like RegisterHotKey, which doesn't allow the same combination twice
+        let in_use = keyboard_hook.as_ref().is_some_and(|kh| {
+            kh.bindings
+                .iter()
+                .any(|b| b.vk_code == vk_code && b.modifiers == modifiers)
+        });
+        if in_use {
+            return Err(HkError::AlreadyRegistered {
+                key,
+                modifiers: key_modifiers.to_vec(),
+                id: None,
+            });
+        }
+
+        let keyboard_hook = match &mut *keyboard_hook {
+            Some(keyboard_hook) => keyboard_hook,
+            None => {
+                let hook = unsafe {
+                    SetWindowsHookExW(
+                        WH_KEYBOARD_LL,
+                        Some(keyboard_hook_proc),
+                        GetModuleHandleW(std::ptr::null()),
+                        0,
+                    )
+                };
+                if hook.is_null() {
+                    return Err(HkError::RegistrationFailed(unsafe { GetLastError() }));
+                }
+
+                keyboard_hook.insert(KeyboardHook {
+                    hook,
+                    bindings: Vec::new(),
+                    held: Vec::new(),
+                })
+            }
+        };
+
+        keyboard_hook.bindings.push(KeyBinding {
+            hwnd,
+            thread_id,
+            id,
+            vk_code,
+            modifiers,
+            allow_repeat,
+        });
+        Ok(())
+    })
+}
+
+/// Unregister the suppressing hotkey with the given id of the backend with the given window.
+/// Returns false if there is no such hotkey. The keyboard hook is uninstalled after the last
+/// suppressing hotkey of the thread was unregistered.
+///
+pub(crate) fn unregister(hwnd: usize, id: HotkeyId) -> bool {
+    remove_bindings(|b| b.hwnd == hwnd && b.id == id)
+}
+
+/// Unregister all suppressing hotkeys of the backend with the given window
+///
+pub(crate) fn unregister_window(hwnd: usize) {
+    remove_bindings(|b| b.hwnd == hwnd);
+}
+
+/// Remove all bindings that match the filter and uninstall the hook if none are left. Returns
+/// true if any binding was removed.
+///
+fn remove_bindings(filter: impl Fn(&KeyBinding) -> bool) -> bool {
+    KEYBOARD_HOOK.with(|keyboard_hook| {
+        let mut keyboard_hook = keyboard_hook.borrow_mut();
+        let Some(kh) = keyboard_hook.as_mut() else {
+            return false;
+        };
+
+        let len = kh.bindings.len();
+        kh.bindings.retain(|b| !filter(b));
+        let removed = kh.bindings.len() != len;
+
+        if kh.bindings.is_empty() {
+            unsafe { UnhookWindowsHookEx(kh.hook) };
+            *keyboard_hook = None;
+        }
+
+        removed
+    })
+}
+
+/// The low-level keyboard hook procedure. This is called for every key event of the system and
+/// the input is blocked until it returns, so it only posts the hotkey events and returns right
+/// away. Returning 1 suppresses the key event.
+///
+unsafe extern "system" fn keyboard_hook_proc(code: i32, wparam: WPARAM, lparam: LPARAM) -> LRESULT {
+    if code == HC_ACTION {
+        let vk_code = (*(lparam as *const KBDLLHOOKSTRUCT)).vkCode;
+
+        let suppress = match wparam as u32 {
+            WM_KEYDOWN | WM_SYSKEYDOWN => key_down(vk_code),
+            WM_KEYUP | WM_SYSKEYUP => key_up(vk_code),
+            _ => false,
+        };
+        if suppress {
+            return 1;
+        }
+    }
+
+    CallNextHookEx(std::ptr::null_mut(), code, wparam, lparam)
+}
+
+/// Post the hotkey events for a key down event. Returns true if the key event belongs to a
+/// suppressing hotkey.
+///
+fn key_down(vk_code: u32) -> bool {
+    KEYBOARD_HOOK.with(|keyboard_hook| {
+        // The hook is not called while the bindings are modified, but never panic here
+        let Ok(mut keyboard_hook) = keyboard_hook.try_borrow_mut() else {
+            return false;
+        };
+        let Some(kh) = keyboard_hook.as_mut() else {
+            return false;
+        };
+
+        // The modifiers are only looked up if the key is bound at all, since this is called for
+        // every key press
+        if !kh.bindings.iter().any(|b| b.vk_code == vk_code) {
+            return false;
+        }
+        let modifiers = current_modifiers();
+        let Some(binding) = kh
+            .bindings
+            .iter()
+            .find(|b| b.vk_code == vk_code && b.modifiers == modifiers)
+        else {
+            return false;
+        };
+
+        // Auto-repeat sends more key down events while the key is held down
+        let repeated = kh.held.contains(&vk_code);
+        if !repeated || binding.allow_repeat {
+            let id = binding.id.0 as WPARAM;
+            let trigger = HotkeyTrigger::new(VKey::CustomKeyCode(vk_code as i32), &[]);
+            // The modifiers of the binding are the ones that are held down
+            let lparam = trigger.to_lparam() | binding.modifiers as LPARAM;
+            unsafe {
+                if binding.hwnd == 0 {
+                    PostThreadMessageW(binding.thread_id, WM_HOTKEY, id, lparam);
+                } else {
+                    PostMessageW(binding.hwnd as HWND, WM_HOTKEY, id, lparam);
+                }
+            }
+        }
+        if !repeated {
+            kh.held.push(vk_code);
+        }
+
+        true
+    })
+}
+
+/// Returns true if the key up event belongs to a key down event that was suppressed.
+///
+fn key_up(vk_code: u32) -> bool {
+    KEYBOARD_HOOK.with(|keyboard_hook| {
+        let Ok(mut keyboard_hook) = keyboard_hook.try_borrow_mut() else {
+            return false;
+        };
+        let Some(kh) = keyboard_hook.as_mut() else {
+            return false;
+        };
+
+        let len = kh.held.len();
+        kh.held.retain(|&held| held != vk_code);
+        kh.held.len() != len
+    })
+}
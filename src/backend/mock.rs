@@ -0,0 +1,202 @@
+use std::{
+    collections::HashSet,
+    sync::{Arc, Mutex, MutexGuard},
+    time::Duration,
+};
+
+use crate::{
+    backend::{BackendMessage, HotkeyBackend, HotkeyTrigger, StubBackend},
+    error::HkError,
+    keys::{ModKey, VKey},
+    HotkeyId, InterruptHandle, LoopQuitter,
+};
+
+/// Windows error code that the `MockBackend` reports for registrations that were told to fail
+pub(crate) const MOCK_REGISTER_ERROR: u32 = 1409;
+/// Windows error code that the `MockBackend` reports for unregistrations that were told to fail
+pub(crate) const MOCK_UNREGISTER_ERROR: u32 = 1419;
+
+/// A call of the `HotkeyBackend` functions, recorded by the `MockBackend`
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Call {
+    Register(HotkeyId, VKey, Vec<ModKey>),
+    Unregister(HotkeyId),
+}
+
+/// The `HotkeyBackend` for the unit tests. It behaves like the `StubBackend`, which provides the
+/// message queue and key states, and additionally records every register and unregister call and
+/// can be told to fail them.
+///
+#[derive(Clone, Default)]
+pub(crate) struct MockBackend {
+    stub: StubBackend,
+    state: Arc<Mutex<MockState>>,
+}
+
+#[derive(Default)]
+struct MockState {
+    calls: Vec<Call>,
+    /// Keycodes of the main keys whose registration fails
+    failing_keys: HashSet<i32>,
+    /// Ids whose registration fails
+    failing_register_ids: HashSet<HotkeyId>,
+    /// Ids whose unregistration fails
+    failing_ids: HashSet<HotkeyId>,
+    /// The thread that the hotkeys are registered for, see `HotkeyBackend::owner_thread_id`
+    owner_thread_id: Option<u32>,
+}
+
+impl MockBackend {
+    pub(crate) fn new() -> MockBackend {
+        MockBackend::default()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, MockState> {
+        self.state.lock().unwrap()
+    }
+
+    /// Make every registration with the given main key fail with `HkError::RegistrationFailed`,
+    /// or succeed again.
+    ///
+    pub(crate) fn fail_register(&self, key: VKey, fail: bool) {
+        let mut state = self.lock();
+        if fail {
+            state.failing_keys.insert(key.to_vk_code());
+        } else {
+            state.failing_keys.remove(&key.to_vk_code());
+        }
+    }
+
+    /// Make every registration with the given id fail with `HkError::RegistrationFailed`, or
+    /// succeed again.
+    ///
+    pub(crate) fn fail_register_id(&self, id: HotkeyId, fail: bool) {
+        let mut state = self.lock();
+        if fail {
+            state.failing_register_ids.insert(id);
+        } else {
+            state.failing_register_ids.remove(&id);
+        }
+    }
+
+    /// Make every unregistration of the given id fail with `HkError::UnregistrationFailed`, or
+    /// succeed again.
+    ///
+    pub(crate) fn fail_unregister(&self, id: HotkeyId, fail: bool) {
+        let mut state = self.lock();
+        if fail {
+            state.failing_ids.insert(id);
+        } else {
+            state.failing_ids.remove(&id);
+        }
+    }
+
+    /// Set the thread that the backend reports as the owner of the hotkeys. By default the backend
+    /// works on any thread.
+    ///
+    pub(crate) fn set_owner_thread_id(&self, owner: Option<u32>) {
+        self.lock().owner_thread_id = owner;
+    }
+
+    /// Get all register and unregister calls in the order they were made, including the failed
+    /// ones.
+    ///
+    pub(crate) fn calls(&self) -> Vec<Call> {
+        self.lock().calls.clone()
+    }
+
+    /// Get the ids that are currently registered with the backend, ordered by id.
+    ///
+    pub(crate) fn registered_ids(&self) -> Vec<HotkeyId> {
+        self.stub.registered_ids()
+    }
+
+    /// Queue a hotkey event that doesn't report its trigger.
+    ///
+    pub(crate) fn push_hotkey(&self, id: HotkeyId) {
+        self.stub.push_hotkey(id);
+    }
+
+    /// Queue a hotkey event that reports its trigger through the given `lParam` of a
+    /// `WM_HOTKEY` message.
+    ///
+    pub(crate) fn push_hotkey_lparam(&self, id: HotkeyId, lparam: isize) {
+        let trigger = HotkeyTrigger::from_lparam(lparam);
+        self.stub
+            .push_message(BackendMessage::Hotkey(id, Some(trigger)));
+    }
+
+    /// Set whether the given key is reported as pressed.
+    ///
+    pub(crate) fn set_key_state(&self, key: VKey, pressed: bool) {
+        self.stub.set_key_state(key, pressed);
+    }
+}
+
+impl HotkeyBackend for MockBackend {
+    fn register_hotkey(
+        &self,
+        id: HotkeyId,
+        key: VKey,
+        key_modifiers: &[ModKey],
+        allow_repeat: bool,
+    ) -> Result<(), HkError> {
+        let mut state = self.lock();
+        state
+            .calls
+            .push(Call::Register(id, key, key_modifiers.to_vec()));
+        if state.failing_keys.contains(&key.to_vk_code())
+            || state.failing_register_ids.contains(&id)
+        {
+            return Err(HkError::RegistrationFailed(MOCK_REGISTER_ERROR));
+        }
+        drop(state);
+
+        self.stub
+            .register_hotkey(id, key, key_modifiers, allow_repeat)
+    }
+
+    fn unregister_hotkey(&self, id: HotkeyId) -> Result<(), HkError> {
+        let mut state = self.lock();
+        state.calls.push(Call::Unregister(id));
+        if state.failing_ids.contains(&id) {
+            return Err(HkError::UnregistrationFailed(MOCK_UNREGISTER_ERROR));
+        }
+        drop(state);
+
+        self.stub.unregister_hotkey(id)
+    }
+
+    fn get_message(&self) -> Result<BackendMessage, HkError> {
+        self.stub.get_message()
+    }
+
+    fn peek_message(&self, hotkeys_only: bool) -> Option<BackendMessage> {
+        self.stub.peek_message(hotkeys_only)
+    }
+
+    fn wait_message(&self, timeout: Duration) -> Result<bool, HkError> {
+        self.stub.wait_message(timeout)
+    }
+
+    fn key_state(&self, key: VKey) -> bool {
+        self.stub.key_state(key)
+    }
+
+    fn post_hotkey(&self, id: HotkeyId, trigger: HotkeyTrigger) -> Result<(), HkError> {
+        self.stub.post_hotkey(id, trigger)
+    }
+
+    fn interrupt_handle(&self) -> InterruptHandle {
+        self.stub.interrupt_handle()
+    }
+
+    fn quit_handle(&self) -> LoopQuitter {
+        self.stub.quit_handle()
+    }
+
+    fn owner_thread_id(&self) -> Option<u32> {
+        self.lock().owner_thread_id
+    }
+}
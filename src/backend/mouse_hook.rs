@@ -0,0 +1,197 @@
+use std::cell::RefCell;
+
+use winapi::shared::minwindef::{HIWORD, LPARAM, LRESULT, WPARAM};
+use winapi::shared::windef::{HHOOK, HWND};
+use winapi::um::errhandlingapi::GetLastError;
+use winapi::um::libloaderapi::GetModuleHandleW;
+use winapi::um::winuser::{
+    CallNextHookEx, PostMessageW, PostThreadMessageW, SetWindowsHookExW, UnhookWindowsHookEx,
+    HC_ACTION, MSLLHOOKSTRUCT, WH_MOUSE_LL, WM_HOTKEY, WM_LBUTTONDOWN, WM_MBUTTONDOWN,
+    WM_RBUTTONDOWN, WM_XBUTTONDOWN, XBUTTON1, XBUTTON2,
+};
+
+use crate::{
+    backend::{current_modifiers, HotkeyTrigger},
+    error::HkError,
+    keys::ModKey,
+    mouse::MouseButton,
+    HotkeyId,
+};
+
+/// A mouse hotkey of a `Win32Backend`
+///
+struct MouseBinding {
+    /// The window of the backend that receives the hotkey events, or 0 for the thread queue
+    hwnd: usize,
+    /// The thread of the backend, used if there is no window
+    thread_id: u32,
+    id: HotkeyId,
+    button: MouseButton,
+    /// The combined modifier codes, see `ModKey::combine`
+    modifiers: u32,
+}
+
+/// The installed mouse hook of the current thread and the mouse hotkeys of all backends on this
+/// thread
+///
+struct MouseHook {
+    hook: HHOOK,
+    bindings: Vec<MouseBinding>,
+}
+
+thread_local! {
+    /// Low-level hooks are called on the thread that installed them and don't get any user data,
+    /// so the hotkeys are stored per thread
+    static MOUSE_HOOK: RefCell<Option<MouseHook>> = const { RefCell::new(None) };
+}
+
+/// Register a mouse hotkey for the backend with the given window and thread. The mouse hook is
+/// installed for the first mouse hotkey of the thread.
+///
+pub(crate) fn register(
+    hwnd: usize,
+    thread_id: u32,
+    id: HotkeyId,
+    button: MouseButton,
+    key_modifiers: &[ModKey],
+) -> Result<(), HkError> {
+    let modifiers = ModKey::combine(key_modifiers);
+
+    MOUSE_HOOK.with(|mouse_hook| {
+        let mut mouse_hook = mouse_hook.borrow_mut();
+
+        // Behave like RegisterHotKey, which doesn't allow the same combination twice
+        let in_use = mouse_hook.as_ref().is_some_and(|mh| {
+            mh.bindings
+                .iter()
+                .any(|b| b.button == button && b.modifiers == modifiers)
+        });
+        if in_use {
+            return Err(HkError::AlreadyRegistered {
+                key: button.to_vkey(),
+                modifiers: key_modifiers.to_vec(),
+                id: None,
+            });
+        }
+
+        let mouse_hook = match &mut *mouse_hook {
+            Some(mouse_hook) => mouse_hook,
+            None => {
+                let hook = unsafe {
+                    SetWindowsHookExW(
+                        WH_MOUSE_LL,
+                        Some(mouse_hook_proc),
+                        GetModuleHandleW(std::ptr::null()),
+                        0,
+                    )
+                };
+                if hook.is_null() {
+                    return Err(HkError::RegistrationFailed(unsafe { GetLastError() }));
+                }
+
+                mouse_hook.insert(MouseHook {
+                    hook,
+                    bindings: Vec::new(),
+                })
+            }
+        };
+
+        mouse_hook.bindings.push(MouseBinding {
+            hwnd,
+            thread_id,
+            id,
+            button,
+            modifiers,
+        });
+        Ok(())
+    })
+}
+
+/// Unregister the mouse hotkey with the given id of the backend with the given window. Returns
+/// false if there is no such mouse hotkey. The mouse hook is uninstalled after the last mouse
+/// hotkey of the thread was unregistered.
+///
+pub(crate) fn unregister(hwnd: usize, id: HotkeyId) -> bool {
+    remove_bindings(|b| b.hwnd == hwnd && b.id == id)
+}
+
+/// Unregister all mouse hotkeys of the backend with the given window
+///
+pub(crate) fn unregister_window(hwnd: usize) {
+    remove_bindings(|b| b.hwnd == hwnd);
+}
+
+/// Remove all bindings that match the filter and uninstall the hook if none are left. Returns
+/// true if any binding was removed.
+///
+fn remove_bindings(filter: impl Fn(&MouseBinding) -> bool) -> bool {
+    MOUSE_HOOK.with(|mouse_hook| {
+        let mut mouse_hook = mouse_hook.borrow_mut();
+        let Some(mh) = mouse_hook.as_mut() else {
+            return false;
+        };
+
+        let len = mh.bindings.len();
+        mh.bindings.retain(|b| !filter(b));
+        let removed = mh.bindings.len() != len;
+
+        if mh.bindings.is_empty() {
+            unsafe { UnhookWindowsHookEx(mh.hook) };
+            *mouse_hook = None;
+        }
+
+        removed
+    })
+}
+
+/// The low-level mouse hook procedure. This is called for every mouse event, so it only posts
+/// the hotkey events and returns right away.
+///
+unsafe extern "system" fn mouse_hook_proc(code: i32, wparam: WPARAM, lparam: LPARAM) -> LRESULT {
+    if code == HC_ACTION {
+        if let Some(button) = pressed_button(wparam as u32, lparam) {
+            let modifiers = current_modifiers();
+
+            MOUSE_HOOK.with(|mouse_hook| {
+                // The hook is not called while the bindings are modified, but never panic here
+                let Ok(mouse_hook) = mouse_hook.try_borrow() else {
+                    return;
+                };
+
+                let bindings = mouse_hook.iter().flat_map(|mh| &mh.bindings);
+                for b in bindings.filter(|b| b.button == button && b.modifiers == modifiers) {
+                    let id = b.id.0 as WPARAM;
+                    let trigger = HotkeyTrigger::new(button.to_vkey(), &[]);
+                    // The modifiers of the binding are the ones that are held down
+                    let lparam = trigger.to_lparam() | b.modifiers as LPARAM;
+                    if b.hwnd == 0 {
+                        PostThreadMessageW(b.thread_id, WM_HOTKEY, id, lparam);
+                    } else {
+                        PostMessageW(b.hwnd as HWND, WM_HOTKEY, id, lparam);
+                    }
+                }
+            });
+        }
+    }
+
+    CallNextHookEx(std::ptr::null_mut(), code, wparam, lparam)
+}
+
+/// Get the mouse button that was pressed down by the mouse message
+///
+unsafe fn pressed_button(msg: u32, lparam: LPARAM) -> Option<MouseButton> {
+    Some(match msg {
+        WM_LBUTTONDOWN => MouseButton::Left,
+        WM_RBUTTONDOWN => MouseButton::Right,
+        WM_MBUTTONDOWN => MouseButton::Middle,
+        WM_XBUTTONDOWN => {
+            let info = &*(lparam as *const MSLLHOOKSTRUCT);
+            match HIWORD(info.mouseData) {
+                XBUTTON1 => MouseButton::XButton1,
+                XBUTTON2 => MouseButton::XButton2,
+                _ => return None,
+            }
+        }
+        _ => return None,
+    })
+}
@@ -0,0 +1,251 @@
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    sync::{Arc, Condvar, Mutex, MutexGuard},
+    time::{Duration, Instant},
+};
+
+use crate::{
+    backend::{BackendMessage, HotkeyBackend, HotkeyTrigger},
+    error::HkError,
+    keys::{ModKey, VKey},
+    HotkeyId, InterruptHandle, LoopQuitter,
+};
+
+/// Windows error code for unregistering a hotkey that is not registered
+const ERROR_HOTKEY_NOT_REGISTERED: u32 = 1419;
+
+/// A `HotkeyBackend` that doesn't use any operating system calls. The registered hotkeys are only
+/// recorded and the hotkey events and key states are provided manually using `push_hotkey` and
+/// `set_key_state`. This allows testing the hotkey handling logic on any platform.
+///
+/// The `StubBackend` can be cloned to keep access to the recorded state after moving it into a
+/// `HotkeyManager`. All clones share the same state.
+///
+/// On other platforms than windows, an unsupported `StubBackend` is used by `HotkeyManager::new`,
+/// so registering hotkeys fails with `HkError::Unsupported`.
+///
+#[derive(Clone)]
+pub struct StubBackend {
+    state: Arc<StubState>,
+}
+
+struct StubState {
+    /// Whether registering hotkeys is supported
+    supported: bool,
+    inner: Mutex<StubInner>,
+    /// Notified whenever a new message is pushed into the queue
+    wakeup: Condvar,
+}
+
+#[derive(Default)]
+struct StubInner {
+    /// The registered hotkeys with their key combination
+    registered: HashMap<HotkeyId, (VKey, Vec<ModKey>)>,
+    /// The message queue
+    messages: VecDeque<BackendMessage>,
+    /// The keycodes of the keys that are pressed
+    pressed: HashSet<i32>,
+}
+
+impl Default for StubBackend {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl StubBackend {
+    /// Create a new `StubBackend` that records the registered hotkeys.
+    ///
+    pub fn new() -> StubBackend {
+        Self::with_support(true)
+    }
+
+    /// Create a new `StubBackend` that fails to register any hotkey with `HkError::Unsupported`.
+    ///
+    pub fn unsupported() -> StubBackend {
+        Self::with_support(false)
+    }
+
+    fn with_support(supported: bool) -> StubBackend {
+        StubBackend {
+            state: Arc::new(StubState {
+                supported,
+                inner: Mutex::new(StubInner::default()),
+                wakeup: Condvar::new(),
+            }),
+        }
+    }
+
+    /// Queue a hotkey event for the hotkey with the given id, as if the hotkey was pressed. The
+    /// event doesn't report which keys triggered it.
+    ///
+    pub fn push_hotkey(&self, id: HotkeyId) {
+        self.state.push(BackendMessage::Hotkey(id, None));
+    }
+
+    /// Queue a hotkey event for the hotkey with the given id that reports the given key
+    /// combination as the trigger, like the `lParam` of a `WM_HOTKEY` message.
+    ///
+    pub fn push_hotkey_trigger(&self, id: HotkeyId, key: VKey, key_modifiers: &[ModKey]) {
+        let trigger = HotkeyTrigger::new(key, key_modifiers);
+        self.state.push(BackendMessage::Hotkey(id, Some(trigger)));
+    }
+
+    /// Queue an arbitrary message.
+    ///
+    pub fn push_message(&self, msg: BackendMessage) {
+        self.state.push(msg);
+    }
+
+    /// Set whether the given key is reported as pressed. This is used to check the extra keys
+    /// and forbidden keys of the hotkeys.
+    ///
+    pub fn set_key_state(&self, key: VKey, pressed: bool) {
+        let mut inner = self.state.lock();
+        if pressed {
+            inner.pressed.insert(key.to_vk_code());
+        } else {
+            inner.pressed.remove(&key.to_vk_code());
+        }
+    }
+
+    /// Get the ids of all hotkeys that are currently registered with the backend, ordered by id.
+    ///
+    pub fn registered_ids(&self) -> Vec<HotkeyId> {
+        let mut ids: Vec<_> = self.state.lock().registered.keys().copied().collect();
+        ids.sort_by_key(|id| id.0);
+
+        ids
+    }
+
+    /// Check if a hotkey with the given id is currently registered with the backend.
+    ///
+    pub fn is_registered(&self, id: HotkeyId) -> bool {
+        self.state.lock().registered.contains_key(&id)
+    }
+}
+
+impl StubState {
+    fn lock(&self) -> MutexGuard<'_, StubInner> {
+        self.inner.lock().unwrap()
+    }
+
+    fn push(&self, msg: BackendMessage) {
+        self.lock().messages.push_back(msg);
+        self.wakeup.notify_all();
+    }
+}
+
+impl HotkeyBackend for StubBackend {
+    fn register_hotkey(
+        &self,
+        id: HotkeyId,
+        key: VKey,
+        key_modifiers: &[ModKey],
+        _allow_repeat: bool,
+    ) -> Result<(), HkError> {
+        if !self.state.supported {
+            return Err(HkError::Unsupported);
+        }
+
+        let mut inner = self.state.lock();
+        let modifiers = ModKey::combine(key_modifiers);
+        let in_use = inner
+            .registered
+            .values()
+            .any(|(k, mods)| *k == key && ModKey::combine(mods) == modifiers);
+        if in_use {
+            return Err(HkError::AlreadyRegistered {
+                key,
+                modifiers: key_modifiers.to_vec(),
+                id: None,
+            });
+        }
+
+        inner.registered.insert(id, (key, key_modifiers.to_vec()));
+        Ok(())
+    }
+
+    #[cfg(feature = "hook")]
+    fn register_suppressing_hotkey(
+        &self,
+        id: HotkeyId,
+        key: VKey,
+        key_modifiers: &[ModKey],
+        allow_repeat: bool,
+    ) -> Result<(), HkError> {
+        // There are no real key events that could be suppressed
+        self.register_hotkey(id, key, key_modifiers, allow_repeat)
+    }
+
+    fn unregister_hotkey(&self, id: HotkeyId) -> Result<(), HkError> {
+        match self.state.lock().registered.remove(&id) {
+            Some(_) => Ok(()),
+            None => Err(HkError::UnregistrationFailed(ERROR_HOTKEY_NOT_REGISTERED)),
+        }
+    }
+
+    fn get_message(&self) -> Result<BackendMessage, HkError> {
+        let mut inner = self.state.lock();
+        loop {
+            if let Some(msg) = inner.messages.pop_front() {
+                return Ok(msg);
+            }
+            inner = self.state.wakeup.wait(inner).unwrap();
+        }
+    }
+
+    fn peek_message(&self, hotkeys_only: bool) -> Option<BackendMessage> {
+        let mut inner = self.state.lock();
+        let pos = if hotkeys_only {
+            inner
+                .messages
+                .iter()
+                .position(|msg| matches!(msg, BackendMessage::Hotkey(..)))?
+        } else {
+            0
+        };
+
+        inner.messages.remove(pos)
+    }
+
+    fn wait_message(&self, timeout: Duration) -> Result<bool, HkError> {
+        // If the deadline can't be represented, the timeout is effectively infinite
+        let deadline = Instant::now().checked_add(timeout);
+
+        let mut inner = self.state.lock();
+        while inner.messages.is_empty() {
+            inner = match deadline {
+                Some(deadline) => {
+                    let remaining = deadline.saturating_duration_since(Instant::now());
+                    if remaining.is_zero() {
+                        return Ok(false);
+                    }
+                    self.state.wakeup.wait_timeout(inner, remaining).unwrap().0
+                }
+                None => self.state.wakeup.wait(inner).unwrap(),
+            };
+        }
+
+        Ok(true)
+    }
+
+    fn key_state(&self, key: VKey) -> bool {
+        self.state.lock().pressed.contains(&key.to_vk_code())
+    }
+
+    fn post_hotkey(&self, id: HotkeyId, trigger: HotkeyTrigger) -> Result<(), HkError> {
+        self.state.push(BackendMessage::Hotkey(id, Some(trigger)));
+        Ok(())
+    }
+
+    fn interrupt_handle(&self) -> InterruptHandle {
+        let state = self.state.clone();
+        InterruptHandle::new(move || state.push(BackendMessage::Interrupt))
+    }
+
+    fn quit_handle(&self) -> LoopQuitter {
+        let state = self.state.clone();
+        LoopQuitter::new(move || state.push(BackendMessage::Quit))
+    }
+}
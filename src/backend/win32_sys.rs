@@ -0,0 +1,313 @@
+use std::time::Duration;
+
+use windows_sys::Win32::Foundation::{
+    GetLastError, ERROR_HOTKEY_ALREADY_REGISTERED, FALSE, HWND, LPARAM, WAIT_OBJECT_0,
+    WAIT_TIMEOUT, WPARAM,
+};
+use windows_sys::Win32::System::LibraryLoader::GetModuleHandleA;
+use windows_sys::Win32::System::Threading::GetCurrentThreadId;
+use windows_sys::Win32::UI::Input::KeyboardAndMouse::{
+    RegisterHotKey, UnregisterHotKey, MOD_NOREPEAT,
+};
+use windows_sys::Win32::UI::WindowsAndMessaging::{
+    CreateWindowExA, DestroyWindow, GetMessageW, MsgWaitForMultipleObjects, PeekMessageW,
+    PostMessageW, PostThreadMessageW, HWND_MESSAGE, MSG, PM_REMOVE, QS_HOTKEY, QS_POSTMESSAGE,
+    WM_HOTKEY, WM_NULL, WM_QUIT, WS_DISABLED, WS_EX_NOACTIVATE,
+};
+
+#[cfg(feature = "hook")]
+use crate::backend::keyboard_hook;
+#[cfg(feature = "mouse")]
+use crate::{backend::mouse_hook, mouse::MouseButton};
+use crate::{
+    backend::{timeout_to_ms, BackendMessage, HotkeyBackend, HotkeyTrigger},
+    error::HkError,
+    get_global_keystate,
+    keys::{ModKey, VKey},
+    HotkeyId, InterruptHandle, LoopQuitter,
+};
+
+/// Low-level hooks are called through sent messages, so waiting for messages also needs to wake up
+/// for sent messages while one of the hooks can be installed
+#[cfg(any(feature = "mouse", feature = "hook"))]
+const HOOK_WAKE_MASK: u32 = windows_sys::Win32::UI::WindowsAndMessaging::QS_SENDMESSAGE;
+#[cfg(not(any(feature = "mouse", feature = "hook")))]
+const HOOK_WAKE_MASK: u32 = 0;
+
+/// The `HotkeyBackend` that uses the windows API through the `windows-sys` bindings. The hotkeys
+/// are registered to a hidden window that is created for every backend, so the backend must stay
+/// on the thread that created it.
+///
+pub struct Win32Backend {
+    /// Handle to the hidden window that is used to receive the hotkey events
+    hwnd: HwndDropper,
+    /// Id of the thread that created the backend and receives the hotkey events
+    thread_id: u32,
+}
+
+impl Default for Win32Backend {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Win32Backend {
+    /// Create a new backend for the current thread.
+    ///
+    pub fn new() -> Win32Backend {
+        // Try to create a hidden window to receive the hotkey events for the HotkeyManager.
+        // If the window creation fails, HWND 0 (null) is used which registers hotkeys to the thread
+        // message queue and gets messages from all thread associated windows
+        let hwnd = create_hidden_window().unwrap_or(HwndDropper(std::ptr::null_mut()));
+        Win32Backend {
+            hwnd,
+            thread_id: unsafe { GetCurrentThreadId() },
+        }
+    }
+
+    /// Convert a message from the message queue into a `BackendMessage`
+    ///
+    fn convert_message(msg: &MSG) -> BackendMessage {
+        match msg.message {
+            WM_HOTKEY => BackendMessage::Hotkey(
+                HotkeyId(msg.wParam as i32),
+                Some(
+                    HotkeyTrigger::from_lparam(msg.lParam)
+                        .with_message_info(msg.time, (msg.pt.x, msg.pt.y)),
+                ),
+            ),
+            WM_NULL => BackendMessage::Interrupt,
+            WM_QUIT => BackendMessage::Quit,
+            _ => BackendMessage::Other,
+        }
+    }
+}
+
+impl HotkeyBackend for Win32Backend {
+    fn register_hotkey(
+        &self,
+        id: HotkeyId,
+        key: VKey,
+        key_modifiers: &[ModKey],
+        allow_repeat: bool,
+    ) -> Result<(), HkError> {
+        // Mouse buttons are not supported by RegisterHotKey and are handled by the mouse hook
+        #[cfg(feature = "mouse")]
+        if let Some(button) = MouseButton::from_vkey(key) {
+            let hwnd = self.hwnd.0 as usize;
+            return mouse_hook::register(hwnd, self.thread_id, id, button, key_modifiers);
+        }
+
+        let mut modifiers = ModKey::combine(key_modifiers);
+        if !allow_repeat {
+            modifiers |= MOD_NOREPEAT;
+        }
+
+        // Try to register the hotkey combination with windows
+        let reg_ok =
+            unsafe { RegisterHotKey(self.hwnd.0, id.0, modifiers, key.to_vk_code() as u32) };
+
+        if reg_ok == 0 {
+            // Get the error code right away, before any other API call can overwrite it
+            match unsafe { GetLastError() } {
+                ERROR_HOTKEY_ALREADY_REGISTERED => Err(HkError::AlreadyRegistered {
+                    key,
+                    modifiers: key_modifiers.to_vec(),
+                    id: None,
+                }),
+                err => Err(HkError::RegistrationFailed(err)),
+            }
+        } else {
+            Ok(())
+        }
+    }
+
+    #[cfg(feature = "hook")]
+    fn register_suppressing_hotkey(
+        &self,
+        id: HotkeyId,
+        key: VKey,
+        key_modifiers: &[ModKey],
+        allow_repeat: bool,
+    ) -> Result<(), HkError> {
+        let hwnd = self.hwnd.0 as usize;
+        keyboard_hook::register(hwnd, self.thread_id, id, key, key_modifiers, allow_repeat)
+    }
+
+    fn unregister_hotkey(&self, id: HotkeyId) -> Result<(), HkError> {
+        #[cfg(feature = "mouse")]
+        if mouse_hook::unregister(self.hwnd.0 as usize, id) {
+            return Ok(());
+        }
+        #[cfg(feature = "hook")]
+        if keyboard_hook::unregister(self.hwnd.0 as usize, id) {
+            return Ok(());
+        }
+
+        let ok = unsafe { UnregisterHotKey(self.hwnd.0, id.0) };
+
+        match ok {
+            0 => Err(HkError::UnregistrationFailed(unsafe { GetLastError() })),
+            _ => Ok(()),
+        }
+    }
+
+    fn post_hotkey(&self, id: HotkeyId, trigger: HotkeyTrigger) -> Result<(), HkError> {
+        let wparam = id.0 as WPARAM;
+        let lparam = trigger.to_lparam() as LPARAM;
+
+        // Without a window, the hotkeys are registered to the thread message queue
+        let ok = unsafe {
+            if self.hwnd.0.is_null() {
+                PostThreadMessageW(self.thread_id, WM_HOTKEY, wparam, lparam)
+            } else {
+                PostMessageW(self.hwnd.0, WM_HOTKEY, wparam, lparam)
+            }
+        };
+
+        match ok {
+            0 => Err(HkError::Os(unsafe { GetLastError() })),
+            _ => Ok(()),
+        }
+    }
+
+    fn get_message(&self) -> Result<BackendMessage, HkError> {
+        let mut msg = std::mem::MaybeUninit::<MSG>::uninit();
+
+        // Block and read a message from the message queue. Filtered to receive messages from
+        // WM_NULL to WM_HOTKEY
+        let ok = unsafe { GetMessageW(msg.as_mut_ptr(), self.hwnd.0, WM_NULL, WM_HOTKEY) };
+
+        match ok {
+            // On error the message is not initialized and must not be read
+            -1 => Err(HkError::Os(unsafe { GetLastError() })),
+            // WM_QUIT was received
+            0 => Ok(BackendMessage::Quit),
+            _ => Ok(Self::convert_message(unsafe { msg.assume_init_ref() })),
+        }
+    }
+
+    fn peek_message(&self, hotkeys_only: bool) -> Option<BackendMessage> {
+        let mut msg = std::mem::MaybeUninit::<MSG>::uninit();
+
+        // Filtered to receive messages from WM_NULL to WM_HOTKEY, or only WM_HOTKEY
+        let filter_min = if hotkeys_only { WM_HOTKEY } else { WM_NULL };
+        let ok = unsafe {
+            PeekMessageW(
+                msg.as_mut_ptr(),
+                self.hwnd.0,
+                filter_min,
+                WM_HOTKEY,
+                PM_REMOVE,
+            )
+        };
+
+        match ok {
+            0 => None,
+            _ => Some(Self::convert_message(unsafe { msg.assume_init_ref() })),
+        }
+    }
+
+    fn wait_message(&self, timeout: Duration) -> Result<bool, HkError> {
+        // Block until a new hotkey or posted message (used for interrupts) arrives in the
+        // queue, or the timeout runs out
+        let wait = unsafe {
+            MsgWaitForMultipleObjects(
+                0,
+                std::ptr::null(),
+                FALSE,
+                timeout_to_ms(timeout),
+                QS_HOTKEY | QS_POSTMESSAGE | HOOK_WAKE_MASK,
+            )
+        };
+
+        match wait {
+            WAIT_OBJECT_0 => Ok(true),
+            WAIT_TIMEOUT => Ok(false),
+            _ => Err(HkError::Os(unsafe { GetLastError() })),
+        }
+    }
+
+    fn key_state(&self, key: VKey) -> bool {
+        get_global_keystate(key)
+    }
+
+    fn interrupt_handle(&self) -> InterruptHandle {
+        // Window handles can be used from any thread
+        let hwnd = self.hwnd.0 as usize;
+        InterruptHandle::new(move || unsafe {
+            PostMessageW(hwnd as HWND, WM_NULL, 0, 0);
+        })
+    }
+
+    fn quit_handle(&self) -> LoopQuitter {
+        let hwnd = self.hwnd.0 as usize;
+        let thread_id = self.thread_id;
+
+        // The message loop only receives messages for its window, so messages posted to the thread
+        // are only received when there is no window
+        LoopQuitter::new(move || unsafe {
+            if hwnd == 0 {
+                PostThreadMessageW(thread_id, WM_QUIT, 0, 0);
+            } else {
+                PostMessageW(hwnd as HWND, WM_QUIT, 0, 0);
+            }
+        })
+    }
+
+    fn owner_thread_id(&self) -> Option<u32> {
+        Some(self.thread_id)
+    }
+}
+
+#[cfg(any(feature = "mouse", feature = "hook"))]
+impl Drop for Win32Backend {
+    fn drop(&mut self) {
+        #[cfg(feature = "mouse")]
+        mouse_hook::unregister_window(self.hwnd.0 as usize);
+        #[cfg(feature = "hook")]
+        keyboard_hook::unregister_window(self.hwnd.0 as usize);
+    }
+}
+
+/// Wrapper around a HWND windows pointer that destroys the window on drop
+///
+struct HwndDropper(HWND);
+
+impl Drop for HwndDropper {
+    fn drop(&mut self) {
+        if !self.0.is_null() {
+            let _ = unsafe { DestroyWindow(self.0) };
+        }
+    }
+}
+
+/// Try to create a hidden "message-only" window
+///
+fn create_hidden_window() -> Result<HwndDropper, ()> {
+    let hwnd = unsafe {
+        // Get the current module handle
+        let hinstance = GetModuleHandleA(std::ptr::null());
+        CreateWindowExA(
+            WS_EX_NOACTIVATE,
+            // The "Static" class is not intended for windows, but this shouldn't matter since the
+            // window is hidden anyways
+            c"Static".as_ptr().cast(),
+            c"".as_ptr().cast(),
+            WS_DISABLED,
+            0,
+            0,
+            0,
+            0,
+            HWND_MESSAGE,
+            std::ptr::null_mut(),
+            hinstance,
+            std::ptr::null(),
+        )
+    };
+    if hwnd.is_null() {
+        Err(())
+    } else {
+        Ok(HwndDropper(hwnd))
+    }
+}
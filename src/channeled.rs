@@ -0,0 +1,436 @@
+//! Run a `HotkeyManager` on a dedicated background thread and control it from any thread.
+//!
+//! The singlethreaded `HotkeyManager` and its callbacks stay confined to the background thread,
+//! which registers the hotkeys and receives their events. Other threads only interact with it
+//! through values that are safe to share:
+//! - `HotkeyHandle` is `Clone`, `Send` and `Sync` and sends commands to the background thread,
+//!   including arbitrary operations on the `HotkeyManager` through `HotkeyHandle::run`
+//! - `HotkeyThread` is `Send` and receives the callback return values. It can be moved to
+//!   another thread, but only one thread can receive from it
+//!
+//! Sharing a `&mut HotkeyManager` between threads is never necessary, and the singlethreaded
+//! `HotkeyManager` is neither `Send` nor `Sync`.
+//!
+
+use std::{
+    sync::mpsc::{channel, Receiver, RecvError, RecvTimeoutError, Sender, TryRecvError},
+    sync::Arc,
+    thread::{self, spawn, JoinHandle, ThreadId},
+    time::Duration,
+};
+
+use crate::{
+    error::HkError,
+    keys::{ModKey, Modifiers, VKey},
+    singlethreaded, HotkeyId, HotkeyManagerImpl, InterruptHandle, RegisterOptions,
+};
+
+/// An operation that is run on the `HotkeyManager` of the background thread
+type Operation<T> = Box<dyn FnOnce(&mut singlethreaded::HotkeyManager<T>) + Send>;
+
+/// Commands that are sent to the background thread and executed between handling hotkey events
+///
+enum Command<T> {
+    Register {
+        key: VKey,
+        key_modifiers: Vec<ModKey>,
+        extra_keys: Vec<VKey>,
+        options: RegisterOptions,
+        callback: Box<dyn FnMut() -> T + Send + 'static>,
+        chan_ret: Sender<Result<HotkeyId, HkError>>,
+    },
+    Unregister {
+        id: HotkeyId,
+        chan_ret: Sender<Result<(), HkError>>,
+    },
+    /// Run an arbitrary operation on the `HotkeyManager`. The operation sends its own result
+    Run(Operation<T>),
+    Shutdown,
+}
+
+/// Handle to a `HotkeyManager` that runs its event loop on a dedicated background thread. The
+/// return values of the hotkey callbacks are sent to the handle over a channel, so the hotkeys
+/// can be integrated into an application that runs its own main loop.
+///
+/// The hotkeys are registered by the `setup` function passed to `HotkeyThread::spawn`. The setup
+/// runs on the background thread, since windows delivers the hotkey events to the thread that
+/// registered the hotkeys. Hotkeys can also be registered and unregistered later from any thread
+/// using a `HotkeyHandle`.
+///
+/// The background thread is stopped using `shutdown` or when dropping the `HotkeyThread`.
+///
+pub struct HotkeyThread<T> {
+    receiver: Receiver<T>,
+    handle: HotkeyHandle<T>,
+    thread_handle: Option<JoinHandle<Result<(), HkError>>>,
+}
+
+impl<T: Send + 'static> HotkeyThread<T> {
+    /// Spawn a new background thread with a `HotkeyManager`, register the hotkeys using `setup`
+    /// and start handling hotkeys. Every time a hotkey callback is executed, its return value is
+    /// sent over the channel of the returned `HotkeyThread`.
+    ///
+    /// If `setup` returns an error, the background thread is stopped and the error is returned.
+    /// If `setup` panics, `HkError::ThreadStopped` is returned.
+    ///
+    pub fn spawn<F>(setup: F) -> Result<HotkeyThread<T>, HkError>
+    where
+        F: FnOnce(&mut singlethreaded::HotkeyManager<T>) -> Result<(), HkError> + Send + 'static,
+    {
+        let (init_snd, init_rec) = channel();
+        let (snd, receiver) = channel();
+        let (cmd_snd, cmd_rec) = channel();
+
+        let thread_handle = spawn(move || {
+            let mut hkm = singlethreaded::HotkeyManager::new();
+
+            if let Err(err) = setup(&mut hkm) {
+                init_snd.send(Err(err)).unwrap();
+                return Ok(());
+            }
+            init_snd.send(Ok(hkm.interrupt_handle())).unwrap();
+
+            loop {
+                let ret_val = match hkm.handle_hotkey() {
+                    // A panicking callback doesn't stop the background thread
+                    Err(HkError::CallbackPanicked(_)) => continue,
+                    ret_val => ret_val?,
+                };
+
+                match ret_val {
+                    Some(ret_val) => {
+                        if snd.send(ret_val).is_err() {
+                            // The receiving side is gone, so nobody is left to handle the hotkeys
+                            break;
+                        }
+                    }
+                    // The loop is interrupted whenever a command was sent
+                    None => {
+                        if !process_commands(&mut hkm, &cmd_rec) {
+                            break;
+                        }
+                    }
+                }
+            }
+
+            Ok(())
+        });
+
+        // The sending side is dropped without a result if `setup` panicked
+        match init_rec.recv() {
+            Ok(Ok(interrupt)) => Ok(HotkeyThread {
+                receiver,
+                handle: HotkeyHandle {
+                    commands: cmd_snd,
+                    interrupt: Arc::new(interrupt),
+                    loop_thread: thread_handle.thread().id(),
+                },
+                thread_handle: Some(thread_handle),
+            }),
+            Ok(Err(err)) => {
+                let _ = thread_handle.join();
+                Err(err)
+            }
+            Err(_) => {
+                let _ = thread_handle.join();
+                Err(HkError::ThreadStopped)
+            }
+        }
+    }
+}
+
+impl<T> HotkeyThread<T> {
+    /// Get a `HotkeyHandle` that can be used to register and unregister hotkeys on the background
+    /// thread. The handle can be cloned and sent to other threads.
+    ///
+    pub fn handle(&self) -> HotkeyHandle<T> {
+        self.handle.clone()
+    }
+
+    /// Get the receiving side of the channel that the callback return values are sent over.
+    ///
+    pub fn receiver(&self) -> &Receiver<T> {
+        &self.receiver
+    }
+
+    /// Block until the next hotkey callback was executed and return its return value. An error is
+    /// returned if the background thread has stopped.
+    ///
+    pub fn recv(&self) -> Result<T, RecvError> {
+        self.receiver.recv()
+    }
+
+    /// Same as `recv`, but return with `RecvTimeoutError::Timeout` if no hotkey callback was
+    /// executed within `timeout`.
+    ///
+    pub fn recv_timeout(&self, timeout: Duration) -> Result<T, RecvTimeoutError> {
+        self.receiver.recv_timeout(timeout)
+    }
+
+    /// Same as `recv_timeout`, but in the style of `HotkeyManagerImpl::handle_hotkey_timeout`.
+    /// Returns `Ok(None)` if no hotkey callback was executed within `timeout` and
+    /// `HkError::ThreadStopped` if the background thread has stopped, so a shutdown can be told
+    /// apart from a timeout.
+    ///
+    pub fn recv_hotkey_timeout(&self, timeout: Duration) -> Result<Option<T>, HkError> {
+        match self.receiver.recv_timeout(timeout) {
+            Ok(ret_val) => Ok(Some(ret_val)),
+            Err(RecvTimeoutError::Timeout) => Ok(None),
+            Err(RecvTimeoutError::Disconnected) => Err(HkError::ThreadStopped),
+        }
+    }
+
+    /// Get the return value of a hotkey callback that was executed since the last call, without
+    /// blocking. Returns `TryRecvError::Empty` if there is none.
+    ///
+    pub fn try_recv(&self) -> Result<T, TryRecvError> {
+        self.receiver.try_recv()
+    }
+
+    /// Stop the background thread and wait for it to exit. All hotkeys are unregistered when the
+    /// `HotkeyManager` on the background thread is dropped.
+    ///
+    /// If handling the hotkeys failed on the background thread, that error is returned.
+    ///
+    pub fn shutdown(mut self) -> Result<(), HkError> {
+        match self.stop() {
+            Some(Ok(result)) => result,
+            Some(Err(panic)) => std::panic::resume_unwind(panic),
+            None => Ok(()),
+        }
+    }
+
+    /// Tell the background thread to stop and wait for it to exit. Returns `None` if the thread
+    /// was already stopped.
+    ///
+    fn stop(&mut self) -> Option<std::thread::Result<Result<(), HkError>>> {
+        let thread_handle = self.thread_handle.take()?;
+        let _ = self.handle.send(Command::Shutdown);
+
+        Some(thread_handle.join())
+    }
+}
+
+impl<T> Drop for HotkeyThread<T> {
+    fn drop(&mut self) {
+        let _ = self.stop();
+    }
+}
+
+/// Execute all pending commands on the background thread. Returns false if the thread should
+/// stop.
+///
+fn process_commands<T: 'static>(
+    hkm: &mut singlethreaded::HotkeyManager<T>,
+    commands: &Receiver<Command<T>>,
+) -> bool {
+    // The callers wait for the results, so sending only fails if the caller is gone
+    while let Ok(cmd) = commands.try_recv() {
+        match cmd {
+            Command::Register {
+                key,
+                key_modifiers,
+                extra_keys,
+                options,
+                callback,
+                chan_ret,
+            } => {
+                let ret_val = hkm.register_extrakeys_opts(
+                    key,
+                    &key_modifiers,
+                    &extra_keys,
+                    options,
+                    callback,
+                );
+                let _ = chan_ret.send(ret_val);
+            }
+            Command::Unregister { id, chan_ret } => {
+                let ret_val = hkm.unregister(id);
+                let _ = chan_ret.send(ret_val);
+            }
+            Command::Run(operation) => operation(hkm),
+            Command::Shutdown => return false,
+        }
+    }
+
+    true
+}
+
+/// Handle to register and unregister hotkeys on the background thread of a `HotkeyThread` from
+/// any thread. The hotkeys are always registered with windows by the background thread, which
+/// then also receives the hotkey events.
+///
+/// The commands are queued and the event loop of the background thread is interrupted to execute
+/// them. The methods block until the background thread has executed the command. If the
+/// background thread has stopped, `HkError::ThreadStopped` is returned.
+///
+/// The methods can't be used on the background thread itself, for example from a hotkey callback
+/// or an operation passed to `run`, since the thread would wait for itself. They return
+/// `HkError::CalledFromHotkeyThread` there instead of blocking forever. Use the `HotkeyManager`
+/// that is passed to `run` directly.
+///
+pub struct HotkeyHandle<T> {
+    commands: Sender<Command<T>>,
+    interrupt: Arc<InterruptHandle>,
+    /// The background thread that executes the commands
+    loop_thread: ThreadId,
+}
+
+impl<T> Clone for HotkeyHandle<T> {
+    fn clone(&self) -> Self {
+        Self {
+            commands: self.commands.clone(),
+            interrupt: self.interrupt.clone(),
+            loop_thread: self.loop_thread,
+        }
+    }
+}
+
+impl<T> HotkeyHandle<T> {
+    /// Register a new hotkey on the background thread. See `HotkeyManagerImpl::register`.
+    ///
+    pub fn register(
+        &self,
+        key: VKey,
+        key_modifiers: impl Into<Modifiers>,
+        callback: impl Fn() -> T + Send + 'static,
+    ) -> Result<HotkeyId, HkError> {
+        self.register_extrakeys_opts(
+            key,
+            &key_modifiers.into().to_vec(),
+            &[],
+            RegisterOptions::default(),
+            callback,
+        )
+    }
+
+    /// Register a new hotkey with extra keys and options on the background thread. See
+    /// `HotkeyManagerImpl::register_extrakeys_opts`.
+    ///
+    pub fn register_extrakeys_opts(
+        &self,
+        key: VKey,
+        key_modifiers: &[ModKey],
+        extra_keys: &[VKey],
+        options: RegisterOptions,
+        callback: impl FnMut() -> T + Send + 'static,
+    ) -> Result<HotkeyId, HkError> {
+        self.check_thread()?;
+
+        let (chan_ret, ret) = channel();
+        self.send(Command::Register {
+            key,
+            key_modifiers: key_modifiers.to_vec(),
+            extra_keys: extra_keys.to_vec(),
+            options,
+            callback: Box::new(callback),
+            chan_ret,
+        })?;
+
+        ret.recv().map_err(|_| HkError::ThreadStopped)?
+    }
+
+    /// Unregister a hotkey on the background thread. See `HotkeyManagerImpl::unregister`.
+    ///
+    pub fn unregister(&self, id: HotkeyId) -> Result<(), HkError> {
+        self.check_thread()?;
+
+        let (chan_ret, ret) = channel();
+        self.send(Command::Unregister { id, chan_ret })?;
+
+        ret.recv().map_err(|_| HkError::ThreadStopped)?
+    }
+
+    /// Run an operation on the `HotkeyManager` of the background thread and return its result.
+    /// This gives access to all `HotkeyManagerImpl` functions, for example to disable or rebind
+    /// hotkeys. The `HotkeyManager` itself never leaves the background thread.
+    ///
+    /// The operation must not handle hotkeys or run an event loop, since the background thread
+    /// doesn't receive any commands until the operation returns.
+    ///
+    pub fn run<R: Send + 'static>(
+        &self,
+        operation: impl FnOnce(&mut singlethreaded::HotkeyManager<T>) -> R + Send + 'static,
+    ) -> Result<R, HkError> {
+        self.check_thread()?;
+
+        let (chan_ret, ret) = channel();
+        self.send(Command::Run(Box::new(move |hkm| {
+            let _ = chan_ret.send(operation(hkm));
+        })))?;
+
+        ret.recv().map_err(|_| HkError::ThreadStopped)
+    }
+
+    /// Check that the handle is not used on the background thread, which would wait for the
+    /// result of a command that it can never execute.
+    ///
+    fn check_thread(&self) -> Result<(), HkError> {
+        if thread::current().id() == self.loop_thread {
+            return Err(HkError::CalledFromHotkeyThread);
+        }
+
+        Ok(())
+    }
+
+    /// Queue the command and interrupt the event loop, so the command is executed right away.
+    ///
+    fn send(&self, cmd: Command<T>) -> Result<(), HkError> {
+        self.commands
+            .send(cmd)
+            .map_err(|_| HkError::ThreadStopped)?;
+        self.interrupt.interrupt();
+
+        Ok(())
+    }
+}
+
+// The handles are meant to be moved to other threads, so make sure that they stay thread-safe
+const _: () = {
+    const fn assert_send<S: Send>() {}
+    const fn assert_send_sync<S: Send + Sync>() {}
+
+    assert_send::<HotkeyThread<()>>();
+    assert_send_sync::<HotkeyHandle<()>>();
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn panicking_setup_reports_a_stopped_thread() {
+        let res = HotkeyThread::<()>::spawn(|_| panic!("setup failed"));
+
+        assert!(matches!(res, Err(HkError::ThreadStopped)));
+    }
+
+    #[test]
+    fn failed_setup_returns_its_error() {
+        let res = HotkeyThread::<()>::spawn(|_| Err(HkError::IdExhausted));
+
+        assert!(matches!(res, Err(HkError::IdExhausted)));
+    }
+
+    #[test]
+    fn handle_is_rejected_on_the_hotkey_thread() {
+        let hotkey_thread = HotkeyThread::<()>::spawn(|_| Ok(())).unwrap();
+        let handle = hotkey_thread.handle();
+
+        let res = hotkey_thread
+            .handle()
+            .run(move |_| {
+                (
+                    handle.register(VKey::A, ModKey::Ctrl, || ()),
+                    handle.unregister(HotkeyId(1)),
+                    handle.run(|_| ()),
+                )
+            })
+            .unwrap();
+
+        assert_eq!(res.0, Err(HkError::CalledFromHotkeyThread));
+        assert_eq!(res.1, Err(HkError::CalledFromHotkeyThread));
+        assert_eq!(res.2, Err(HkError::CalledFromHotkeyThread));
+        hotkey_thread.shutdown().unwrap();
+    }
+}
@@ -0,0 +1,157 @@
+//! Hotkey bindings that are loaded from a configuration file, for example by launcher apps that
+//! let the users choose their own hotkeys.
+//!
+//! A `HotkeyConfig` is a list of bindings, each with a key combination like `"CTRL + ALT + T"`,
+//! optional extra keys and the name of an action. With the `serde` feature, the config can be
+//! deserialized from any format that serde supports, like TOML or JSON. In TOML every binding is
+//! a `[[bindings]]` table with the `combo`, `extra`, `action` and `enabled` keys. Only `combo`
+//! and `action` are required.
+//!
+//! The action names are mapped to callbacks by `HotkeyManagerImpl::apply_config`, so the config
+//! file only refers to the actions that the application provides.
+//!
+//! The other way around, `HotkeyManagerImpl::dump_config` describes the registered hotkeys as
+//! `BindingDescriptor`s, for example to save the hotkeys that the user configured at runtime.
+//! The descriptors can be collected into a `HotkeyConfig` again, using the label of each hotkey
+//! as its action name.
+//!
+
+use crate::{
+    error::HkError,
+    keys::{KeyCombination, VKey},
+};
+
+/// A list of hotkey bindings, see the module documentation.
+///
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HotkeyConfig {
+    /// The bindings of the config
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub bindings: Vec<BindingConfig>,
+}
+
+/// A single binding of a `HotkeyConfig`. The `Default` binding is enabled.
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BindingConfig {
+    /// The key combination, parsed like `KeyCombination::from_str`
+    pub combo: String,
+    /// The names of additional keys that need to be pressed together with the hotkey, parsed
+    /// like `VKey::from_keyname`
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub extra: Vec<String>,
+    /// The name of the action that is executed by the hotkey
+    pub action: String,
+    /// Whether the hotkey is enabled after registering it, see `HotkeyManagerImpl::disable`.
+    /// Defaults to true
+    #[cfg_attr(feature = "serde", serde(default = "enabled_by_default"))]
+    pub enabled: bool,
+}
+
+impl Default for BindingConfig {
+    fn default() -> Self {
+        BindingConfig {
+            combo: String::new(),
+            extra: Vec::new(),
+            action: String::new(),
+            enabled: true,
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+fn enabled_by_default() -> bool {
+    true
+}
+
+impl BindingConfig {
+    /// Parse the key combination of the binding, including the extra keys.
+    ///
+    pub fn combination(&self) -> Result<KeyCombination, HkError> {
+        let mut combination: KeyCombination = self.combo.parse()?;
+        for name in &self.extra {
+            combination.extra_keys.push(VKey::from_keyname(name)?);
+        }
+
+        Ok(combination)
+    }
+}
+
+/// Description of a registered hotkey without its callback, as returned by
+/// `HotkeyManagerImpl::dump_config`.
+///
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BindingDescriptor {
+    /// The key combination without the extra keys, like `"Ctrl+Alt+T"`
+    pub combo: String,
+    /// The names of the extra keys
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub extra: Vec<String>,
+    /// The label of the hotkey, see `RegisterOptions::label`
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub label: Option<String>,
+    /// Whether the hotkey is enabled, see `HotkeyManagerImpl::disable`
+    pub enabled: bool,
+}
+
+impl From<BindingDescriptor> for BindingConfig {
+    /// Turn the descriptor into a binding that uses the label as action name. Hotkeys without a
+    /// label get an empty action name. Disabled hotkeys stay disabled when the binding is applied.
+    ///
+    fn from(descriptor: BindingDescriptor) -> BindingConfig {
+        BindingConfig {
+            combo: descriptor.combo,
+            extra: descriptor.extra,
+            action: descriptor.label.unwrap_or_default(),
+            enabled: descriptor.enabled,
+        }
+    }
+}
+
+impl FromIterator<BindingDescriptor> for HotkeyConfig {
+    fn from_iter<I: IntoIterator<Item = BindingDescriptor>>(iter: I) -> HotkeyConfig {
+        HotkeyConfig {
+            bindings: iter.into_iter().map(BindingConfig::from).collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bindings_are_enabled_by_default() {
+        assert!(BindingConfig::default().enabled);
+    }
+
+    #[test]
+    fn descriptors_keep_the_enabled_state() {
+        let descriptor = BindingDescriptor {
+            combo: "Ctrl+T".to_string(),
+            extra: vec!["LShift".to_string()],
+            label: Some("terminal".to_string()),
+            enabled: false,
+        };
+
+        let binding = BindingConfig::from(descriptor);
+
+        assert_eq!(
+            binding,
+            BindingConfig {
+                combo: "Ctrl+T".to_string(),
+                extra: vec!["LShift".to_string()],
+                action: "terminal".to_string(),
+                enabled: false,
+            }
+        );
+        assert_eq!(
+            binding.combination().unwrap(),
+            KeyCombination::new(VKey::T, &[crate::keys::ModKey::Ctrl])
+                .with_extra_keys(&[VKey::LShift])
+        );
+    }
+}
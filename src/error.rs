@@ -1,17 +1,108 @@
 use thiserror::Error;
 
-use crate::keys::VKey;
+use crate::{
+    keys::{format_hotkey, ModKey, VKey},
+    HotkeyId,
+};
 
-#[derive(Debug, Error)]
+/// Error type for all the fallible operations of this crate. It implements `std::error::Error`,
+/// so it can be propagated with `?` and boxed as `Box<dyn Error>`.
+///
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
 pub enum HkError {
     #[error("invalid key name `{0}`")]
     InvalidKey(String),
+    #[error("invalid modifier name `{0}`")]
+    InvalidModifier(String),
+    #[error("invalid hotkey `{0}`, expected modifiers and exactly one main key")]
+    InvalidHotkey(String),
     #[error("invalid key char `{0}`")]
     InvalidKeyChar(char),
     #[error("VKey is not a ModKey `{0}`")]
     NotAModkey(VKey),
-    #[error("Hotkey registration failed. Hotkey or Id might be in use already")]
-    RegistrationFailed,
-    #[error("Hotkey unregistration failed")]
-    UnregistrationFailed,
+    /// Registering the hotkey with windows failed. Contains the windows error code from
+    /// `GetLastError`
+    #[error("Hotkey registration failed (os error {0}). Hotkey or Id might be in use already")]
+    RegistrationFailed(u32),
+    /// The hotkey combination is already registered, either by this or by another application.
+    /// Contains the id of the existing hotkey if it was registered by the same `HotkeyManager`
+    #[error("Hotkey {} is already registered", format_hotkey(*.key, .modifiers))]
+    AlreadyRegistered {
+        key: VKey,
+        modifiers: Vec<ModKey>,
+        id: Option<HotkeyId>,
+    },
+    /// Rebinding or replacing the hotkey with the given id failed with the contained error, and
+    /// registering its previous combination again failed as well. The hotkey was disabled and
+    /// keeps its previous combination
+    #[error("Changing hotkey {} failed and its previous combination could not be restored: {error}", .id.as_i32())]
+    RollbackFailed { id: HotkeyId, error: Box<HkError> },
+    /// The hotkey with the given id is disabled, or the `HotkeyManager` is paused
+    #[error("Hotkey {} is not active", .0.as_i32())]
+    Inactive(HotkeyId),
+    /// No hotkey is registered for the combination
+    #[error("Hotkey {} is not registered", format_hotkey(*.key, .modifiers))]
+    NotRegistered { key: VKey, modifiers: Vec<ModKey> },
+    /// Unregistering the hotkey with windows failed. Contains the windows error code from
+    /// `GetLastError`
+    #[error("Hotkey unregistration failed (os error {0})")]
+    UnregistrationFailed(u32),
+    /// All available hotkey ids are in use, no more hotkeys can be registered
+    #[error("No more hotkey ids available")]
+    IdExhausted,
+    /// There is no hotkey with this id registered in the `HotkeyManager`
+    #[error("Unknown hotkey id {}", .0.as_i32())]
+    UnknownId(HotkeyId),
+    /// An operation on multiple hotkeys failed for some of them. Contains the id and error of
+    /// every hotkey that failed
+    #[error("Operation failed for {} hotkeys", .0.len())]
+    Multiple(Vec<(HotkeyId, HkError)>),
+    /// A sequence needs at least one step
+    #[error("The sequence has no steps")]
+    EmptySequence,
+    /// The sequence is a prefix of a registered sequence or the other way around. Contains the
+    /// steps of the registered sequence
+    #[error("The sequence conflicts with the registered sequence {0}")]
+    ConflictingSequence(String),
+    /// The callback of the hotkey with the given id panicked. The panic was caught, so the
+    /// `HotkeyManager` can keep handling hotkeys
+    #[error("The callback of hotkey {} panicked", .0.as_i32())]
+    CallbackPanicked(HotkeyId),
+    /// The action of a config binding is not known to the application, see
+    /// `HotkeyManagerImpl::apply_config`
+    #[error("Unknown action `{0}`")]
+    UnknownAction(String),
+    /// The operation didn't complete within the given timeout
+    #[error("Timed out")]
+    Timeout,
+    /// The `HotkeyManager` was used from another thread than the one that owns its hotkeys.
+    /// Contains the id of the owner thread
+    #[error("The HotkeyManager was used outside of its owner thread {0}")]
+    WrongThread(u32),
+    /// A `HotkeyHandle` was used on the background thread of its `HotkeyThread`, where it would
+    /// wait for itself
+    #[error("The HotkeyHandle can't be used on the hotkey thread")]
+    CalledFromHotkeyThread,
+    /// The background thread that handles the hotkeys has stopped
+    #[error("The hotkey thread has stopped")]
+    ThreadStopped,
+    /// Hotkeys are not supported on this platform
+    #[error("Hotkeys are only supported on windows")]
+    Unsupported,
+    /// A windows API function failed. Contains the windows error code from `GetLastError`
+    #[error("os error {0}")]
+    Os(u32),
+}
+
+impl HkError {
+    /// Turn the errors of an operation on multiple hotkeys into a single result. An empty list
+    /// means that the operation succeeded for all hotkeys.
+    ///
+    pub(crate) fn from_errors(errors: Vec<(HotkeyId, HkError)>) -> Result<(), HkError> {
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(HkError::Multiple(errors))
+        }
+    }
 }
@@ -0,0 +1,138 @@
+//! Helpers to query the foreground window. These can be used as conditions for context sensitive
+//! hotkeys, see `HotkeyManagerImpl::register_conditional` and
+//! `HotkeyManagerImpl::register_for_app`.
+//!
+
+#[cfg(windows)]
+use std::{ffi::OsString, os::windows::ffi::OsStringExt, path::Path};
+
+#[cfg(windows)]
+use winapi::shared::minwindef::{DWORD, FALSE, MAX_PATH};
+#[cfg(windows)]
+use winapi::um::handleapi::CloseHandle;
+#[cfg(windows)]
+use winapi::um::processthreadsapi::OpenProcess;
+#[cfg(windows)]
+use winapi::um::winbase::QueryFullProcessImageNameW;
+#[cfg(windows)]
+use winapi::um::winnt::PROCESS_QUERY_LIMITED_INFORMATION;
+#[cfg(windows)]
+use winapi::um::winuser::{
+    GetForegroundWindow, GetWindowTextLengthW, GetWindowTextW, GetWindowThreadProcessId,
+};
+
+/// Get the title of the current foreground window.
+///
+/// Returns `None` if there is no foreground window or the window has no title.
+///
+/// # Windows API Functions used
+/// - <https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-getforegroundwindow>
+/// - <https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-getwindowtextlengthw>
+/// - <https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-getwindowtextw>
+///
+#[cfg(windows)]
+pub fn foreground_window_title() -> Option<String> {
+    let hwnd = unsafe { GetForegroundWindow() };
+    if hwnd.is_null() {
+        return None;
+    }
+
+    let len = unsafe { GetWindowTextLengthW(hwnd) };
+    if len <= 0 {
+        return None;
+    }
+
+    // Space for the terminating null character
+    let mut buf = vec![0u16; len as usize + 1];
+    let copied = unsafe { GetWindowTextW(hwnd, buf.as_mut_ptr(), buf.len() as i32) };
+    if copied <= 0 {
+        return None;
+    }
+
+    // The title might have changed in between, so never read past the copied characters
+    let copied = (copied as usize).min(buf.len() - 1);
+    Some(String::from_utf16_lossy(&buf[..copied]))
+}
+
+/// Get the executable name of the process that owns the current foreground window, for example
+/// `"notepad.exe"`. Only the file name is returned, without the path to the executable.
+///
+/// Returns `None` if there is no foreground window or the process can't be queried, for example
+/// because it is running with higher privileges.
+///
+/// # Windows API Functions used
+/// - <https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-getforegroundwindow>
+/// - <https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-getwindowthreadprocessid>
+/// - <https://docs.microsoft.com/en-us/windows/win32/api/processthreadsapi/nf-processthreadsapi-openprocess>
+/// - <https://docs.microsoft.com/en-us/windows/win32/api/winbase/nf-winbase-queryfullprocessimagenamew>
+///
+#[cfg(windows)]
+pub fn foreground_exe_name() -> Option<String> {
+    let hwnd = unsafe { GetForegroundWindow() };
+    if hwnd.is_null() {
+        return None;
+    }
+
+    let mut pid: DWORD = 0;
+    unsafe { GetWindowThreadProcessId(hwnd, &mut pid) };
+    if pid == 0 {
+        return None;
+    }
+
+    let process = unsafe { OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, FALSE, pid) };
+    if process.is_null() {
+        return None;
+    }
+
+    let mut buf = [0u16; MAX_PATH];
+    let mut len = buf.len() as DWORD;
+    let ok = unsafe { QueryFullProcessImageNameW(process, 0, buf.as_mut_ptr(), &mut len) };
+    unsafe { CloseHandle(process) };
+
+    if ok == 0 {
+        return None;
+    }
+
+    let path = OsString::from_wide(&buf[..len as usize]);
+    Path::new(&path)
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+}
+
+/// Get the title of the current foreground window. On other platforms than windows, there is no
+/// foreground window and `None` is always returned.
+///
+#[cfg(not(windows))]
+pub fn foreground_window_title() -> Option<String> {
+    None
+}
+
+/// Get the executable name of the process that owns the current foreground window. On other
+/// platforms than windows, there is no foreground window and `None` is always returned.
+///
+#[cfg(not(windows))]
+pub fn foreground_exe_name() -> Option<String> {
+    None
+}
+
+/// Check if the current foreground window belongs to a process with the executable name
+/// `exe_name`, for example `"notepad.exe"`. The names are compared case-insensitively.
+///
+/// # Windows API Functions used
+/// See `foreground_exe_name`
+///
+pub fn is_foreground_app(exe_name: &str) -> bool {
+    foreground_exe_name().is_some_and(|name| name.to_lowercase() == exe_name.to_lowercase())
+}
+
+/// Create a condition for `HotkeyManagerImpl::register_conditional` that is true while the
+/// foreground window belongs to a process with the executable name `exe_name`.
+///
+/// # Note
+/// The condition queries the foreground process using the windows API every time it is
+/// evaluated, so it is slightly slower than a plain hotkey.
+///
+pub fn foreground_app_condition(exe_name: &str) -> impl Fn() -> bool + Send + Sync + 'static {
+    let exe_name = exe_name.to_lowercase();
+    move || foreground_exe_name().is_some_and(|name| name.to_lowercase() == exe_name)
+}
@@ -1,13 +1,77 @@
+mod combination;
+mod modifiers;
 mod modkey;
 mod vkey;
 
+pub use combination::*;
+pub use modifiers::*;
 pub use modkey::*;
 pub use vkey::*;
 
+use crate::error::HkError;
+
+/// Parse a hotkey combination like `"CTRL + ALT + A"` into the main key and its modifier keys.
+///
+/// The combination is split on `+` and surrounding whitespace is ignored. All parts that are
+/// modifier names (see `ModKey::from_keyname`) are collected as modifier keys and the remaining
+/// part is parsed as the main key (see `VKey::from_keyname`). Exactly one main key is required.
+///
+pub fn parse_hotkey(val: &str) -> Result<(VKey, Vec<ModKey>), HkError> {
+    let mut key = None;
+    let mut modifiers = Vec::new();
+
+    for part in val.split('+').map(str::trim) {
+        if let Ok(modkey) = ModKey::from_keyname(part) {
+            modifiers.push(modkey);
+        } else if key.is_none() {
+            key = Some(VKey::from_keyname(part)?);
+        } else {
+            // Only one main key is allowed
+            return Err(HkError::InvalidHotkey(val.to_string()));
+        }
+    }
+
+    match key {
+        Some(key) => Ok((key, modifiers)),
+        None => Err(HkError::InvalidHotkey(val.to_string())),
+    }
+}
+
+/// Format a hotkey combination into a string like `"Ctrl+Alt+A"`. This is the inverse of
+/// `parse_hotkey`.
+///
+/// The modifier keys are always ordered Ctrl, Alt, Shift, Win regardless of the order in
+/// `key_modifiers`, so equivalent combinations are formatted identically. Duplicate modifier keys
+/// are only displayed once.
+///
+pub fn format_hotkey(key: VKey, key_modifiers: &[ModKey]) -> String {
+    const ORDER: [ModKey; 4] = [ModKey::Ctrl, ModKey::Alt, ModKey::Shift, ModKey::Win];
+
+    let mut hotkey = String::new();
+    for modkey in ORDER.iter().filter(|mk| key_modifiers.contains(mk)) {
+        hotkey.push_str(&format!("{}+", modkey));
+    }
+    hotkey.push_str(&key.to_string());
+
+    hotkey
+}
+
 /// Reexport of all `VK_*` and `MOD_*` constants from the `winapi` crate (`winapi::um::winuser`).
 /// Unless there is an actual special reason for using these codes directly, the variants of the
 /// `VKey` and `ModKey` enums should be used to specify keys instead.
 ///
+/// On other platforms than windows, copies of the constants with the same values are provided.
+///
+#[cfg(not(windows))]
+pub mod winapi_keycodes;
+
+/// Reexport of all `VK_*` and `MOD_*` constants from the `winapi` crate (`winapi::um::winuser`).
+/// Unless there is an actual special reason for using these codes directly, the variants of the
+/// `VKey` and `ModKey` enums should be used to specify keys instead.
+///
+/// On other platforms than windows, copies of the constants with the same values are provided.
+///
+#[cfg(windows)]
 pub mod winapi_keycodes {
     pub use winapi::um::winuser::{
         VK_ACCEPT, VK_ADD, VK_APPS, VK_ATTN, VK_BACK, VK_BROWSER_BACK, VK_BROWSER_FAVORITES,
@@ -47,3 +111,52 @@ pub mod winapi_keycodes {
 
     pub use winapi::um::winuser::{MOD_ALT, MOD_CONTROL, MOD_NOREPEAT, MOD_SHIFT, MOD_WIN};
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn modkeys_display_readable_names() {
+        let names: Vec<_> = ModKey::all().iter().map(ModKey::to_string).collect();
+        assert_eq!(names, ["Alt", "Ctrl", "Shift", "Win"]);
+    }
+
+    #[test]
+    fn format_hotkey_orders_the_modifiers() {
+        assert_eq!(
+            format_hotkey(VKey::A, &[ModKey::Shift, ModKey::Ctrl]),
+            "Ctrl+Shift+A"
+        );
+        assert_eq!(
+            format_hotkey(
+                VKey::A,
+                &[ModKey::Win, ModKey::Shift, ModKey::Alt, ModKey::Ctrl]
+            ),
+            format_hotkey(
+                VKey::A,
+                &[ModKey::Ctrl, ModKey::Alt, ModKey::Shift, ModKey::Win]
+            )
+        );
+    }
+
+    #[test]
+    fn format_hotkey_round_trips_through_parse_hotkey() {
+        let modifiers = [ModKey::Alt, ModKey::Win];
+        for key in [
+            VKey::A,
+            VKey::F13,
+            VKey::Return,
+            VKey::Shift,
+            VKey::CustomKeyCode(0x0E),
+        ] {
+            let (parsed_key, parsed_modifiers) =
+                parse_hotkey(&format_hotkey(key, &modifiers)).unwrap();
+            assert_eq!(parsed_key, key);
+            assert_eq!(
+                ModKey::combine(&parsed_modifiers),
+                ModKey::combine(&modifiers)
+            );
+        }
+    }
+}
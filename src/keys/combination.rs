@@ -0,0 +1,150 @@
+use std::{
+    fmt::Display,
+    hash::{Hash, Hasher},
+    str::FromStr,
+};
+
+use crate::{
+    error::HkError,
+    keys::{format_hotkey, ModKey, VKey},
+};
+
+/// A complete hotkey combination consisting of the main key, the modifier keys and the extra keys
+/// that need to be pressed together with the hotkey.
+///
+/// Two `KeyCombination`s are equal if they trigger on the same keys, so the order and duplicates
+/// of the modifier keys and extra keys don't matter. This makes `KeyCombination` usable as a key
+/// in a `HashMap` or `HashSet`, for example to detect conflicting bindings.
+///
+/// The text representation is the modifier keys followed by the extra keys and the main key, all
+/// separated by `+`. For example `"Ctrl+Shift+B+A"` is the main key `A` with the modifiers
+/// `Ctrl` and `Shift` and the extra key `B`.
+///
+#[derive(Debug, Clone)]
+pub struct KeyCombination {
+    /// The main key of the hotkey
+    pub key: VKey,
+    /// The modifier keys of the hotkey
+    pub modifiers: Vec<ModKey>,
+    /// The additional keys that need to be pressed together with the hotkey
+    pub extra_keys: Vec<VKey>,
+}
+
+impl KeyCombination {
+    /// Create a new `KeyCombination` without extra keys.
+    ///
+    pub fn new(key: VKey, key_modifiers: &[ModKey]) -> KeyCombination {
+        KeyCombination {
+            key,
+            modifiers: key_modifiers.to_vec(),
+            extra_keys: Vec::new(),
+        }
+    }
+
+    /// Set the extra keys that need to be pressed together with the hotkey.
+    ///
+    pub fn with_extra_keys(mut self, extra_keys: &[VKey]) -> KeyCombination {
+        self.extra_keys = extra_keys.to_vec();
+        self
+    }
+
+    /// Get the keycodes of the extra keys sorted and without duplicates, which is independent of
+    /// the order the extra keys were specified in
+    ///
+    fn extra_key_codes(&self) -> Vec<i32> {
+        let mut codes: Vec<_> = self.extra_keys.iter().map(VKey::to_vk_code).collect();
+        codes.sort_unstable();
+        codes.dedup();
+
+        codes
+    }
+}
+
+impl PartialEq for KeyCombination {
+    fn eq(&self, other: &KeyCombination) -> bool {
+        self.key == other.key
+            && ModKey::combine(&self.modifiers) == ModKey::combine(&other.modifiers)
+            && self.extra_key_codes() == other.extra_key_codes()
+    }
+}
+
+impl Eq for KeyCombination {}
+
+impl Hash for KeyCombination {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.key.hash(state);
+        ModKey::combine(&self.modifiers).hash(state);
+        self.extra_key_codes().hash(state);
+    }
+}
+
+impl Display for KeyCombination {
+    /// Format the combination like `format_hotkey`, with the extra keys inserted before the main
+    /// key. The extra keys are ordered by their keycode, so equal combinations are formatted
+    /// identically.
+    ///
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let hotkey = format_hotkey(self.key, &self.modifiers);
+
+        // Split off the main key to insert the extra keys in front of it
+        let key = self.key.to_string();
+        let prefix = &hotkey[..hotkey.len() - key.len()];
+        write!(f, "{}", prefix)?;
+
+        for code in self.extra_key_codes() {
+            write!(f, "{}+", VKey::CustomKeyCode(code))?;
+        }
+
+        write!(f, "{}", key)
+    }
+}
+
+impl FromStr for KeyCombination {
+    type Err = HkError;
+
+    /// Parse a combination like `"CTRL + SHIFT + B + A"`. The parts are split on `+` and
+    /// surrounding whitespace is ignored. All modifier names are collected as modifier keys, the
+    /// last remaining key is the main key and all other keys are extra keys.
+    ///
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut modifiers = Vec::new();
+        let mut keys = Vec::new();
+
+        for part in s.split('+').map(str::trim) {
+            if let Ok(modkey) = ModKey::from_keyname(part) {
+                modifiers.push(modkey);
+            } else {
+                keys.push(VKey::from_keyname(part)?);
+            }
+        }
+
+        let key = keys
+            .pop()
+            .ok_or_else(|| HkError::InvalidHotkey(s.to_string()))?;
+
+        Ok(KeyCombination {
+            key,
+            modifiers,
+            extra_keys: keys,
+        })
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for KeyCombination {
+    /// Serialize the `KeyCombination` as its text representation (see `Display`)
+    ///
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for KeyCombination {
+    /// Deserialize the `KeyCombination` from its text representation (see `FromStr`)
+    ///
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let combination = String::deserialize(deserializer)?;
+        combination.parse().map_err(serde::de::Error::custom)
+    }
+}
@@ -0,0 +1,142 @@
+use std::ops::{BitOr, BitOrAssign};
+
+use crate::keys::ModKey;
+
+/// A set of modifier keys, stored as the combined modifier codes that are used by the windows API
+/// (see `ModKey::combine`).
+///
+/// `Modifiers` can be created from a single `ModKey`, a slice or array of `ModKey`s or by
+/// combining `ModKey`s with `|`, for example `ModKey::Ctrl | ModKey::Shift`. Duplicate modifier
+/// keys and their order don't matter.
+///
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Modifiers(u32);
+
+impl Modifiers {
+    /// Create an empty set without any modifier keys.
+    ///
+    pub const fn empty() -> Modifiers {
+        Modifiers(0)
+    }
+
+    /// Create a set from combined modifier codes, for example the low word of the `lParam` of a
+    /// `WM_HOTKEY` message. Bits that don't belong to a modifier key, like `MOD_NOREPEAT`, are
+    /// ignored.
+    ///
+    pub fn from_bits(bits: u32) -> Modifiers {
+        Modifiers(bits & ModKey::combine(&ModKey::all()))
+    }
+
+    /// Get the combined modifier codes for usage with winapi functions.
+    ///
+    pub const fn bits(&self) -> u32 {
+        self.0
+    }
+
+    /// Check if the set contains no modifier keys.
+    ///
+    pub const fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    /// Check if the given modifier key is part of the set.
+    ///
+    pub const fn contains(&self, modifier: ModKey) -> bool {
+        self.0 & modifier.to_mod_code() != 0
+    }
+
+    /// Add a modifier key to the set.
+    ///
+    pub fn insert(&mut self, modifier: ModKey) {
+        self.0 |= modifier.to_mod_code();
+    }
+
+    /// Remove a modifier key from the set.
+    ///
+    pub fn remove(&mut self, modifier: ModKey) {
+        self.0 &= !modifier.to_mod_code();
+    }
+
+    /// Get the modifier keys of the set, in the order of their declaration. See
+    /// `ModKey::from_bits`.
+    ///
+    pub fn to_vec(&self) -> Vec<ModKey> {
+        ModKey::from_bits(self.0)
+    }
+}
+
+impl std::fmt::Debug for Modifiers {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("Modifiers").field(&self.to_vec()).finish()
+    }
+}
+
+impl From<ModKey> for Modifiers {
+    fn from(modifier: ModKey) -> Modifiers {
+        Modifiers(modifier.to_mod_code())
+    }
+}
+
+impl From<&[ModKey]> for Modifiers {
+    fn from(modifiers: &[ModKey]) -> Modifiers {
+        Modifiers(ModKey::combine(modifiers))
+    }
+}
+
+impl<const N: usize> From<[ModKey; N]> for Modifiers {
+    fn from(modifiers: [ModKey; N]) -> Modifiers {
+        Modifiers(ModKey::combine(&modifiers))
+    }
+}
+
+impl<const N: usize> From<&[ModKey; N]> for Modifiers {
+    fn from(modifiers: &[ModKey; N]) -> Modifiers {
+        Modifiers(ModKey::combine(modifiers))
+    }
+}
+
+impl From<&Vec<ModKey>> for Modifiers {
+    fn from(modifiers: &Vec<ModKey>) -> Modifiers {
+        Modifiers(ModKey::combine(modifiers))
+    }
+}
+
+impl From<Vec<ModKey>> for Modifiers {
+    fn from(modifiers: Vec<ModKey>) -> Modifiers {
+        Modifiers(ModKey::combine(&modifiers))
+    }
+}
+
+impl From<Modifiers> for u32 {
+    fn from(modifiers: Modifiers) -> u32 {
+        modifiers.0
+    }
+}
+
+impl FromIterator<ModKey> for Modifiers {
+    fn from_iter<I: IntoIterator<Item = ModKey>>(iter: I) -> Modifiers {
+        iter.into_iter().fold(Modifiers::empty(), |a, b| a | b)
+    }
+}
+
+impl<M: Into<Modifiers>> BitOr<M> for Modifiers {
+    type Output = Modifiers;
+
+    fn bitor(self, rhs: M) -> Modifiers {
+        Modifiers(self.0 | rhs.into().0)
+    }
+}
+
+impl<M: Into<Modifiers>> BitOr<M> for ModKey {
+    type Output = Modifiers;
+
+    fn bitor(self, rhs: M) -> Modifiers {
+        Modifiers::from(self) | rhs
+    }
+}
+
+impl<M: Into<Modifiers>> BitOrAssign<M> for Modifiers {
+    fn bitor_assign(&mut self, rhs: M) {
+        self.0 |= rhs.into().0;
+    }
+}
@@ -1,44 +1,64 @@
-use std::fmt::Display;
+use std::{fmt::Display, str::FromStr};
 
 use crate::{error::HkError, VKey};
 
 /// Modifier Key for hotkeys.
 ///
+/// The modifier keys don't distinguish between the left and right keys. To only trigger a hotkey
+/// for one side, register it with the modifier key and add the key of that side (see
+/// `ModKey::left_key` and `ModKey::right_key`) as an extra key. For example `ModKey::Alt` with the
+/// extra key `VKey::RMenu` for RIGHT ALT only.
+///
 /// See: `fsModifiers` from <https://learn.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-registerhotkey>
 ///
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum ModKey {
+    /// Either ALT key (`MOD_ALT`)
     Alt,
+    /// Either CTRL key (`MOD_CONTROL`)
     Ctrl,
+    /// Either SHIFT key (`MOD_SHIFT`)
     Shift,
+    /// Either WINDOWS key (`MOD_WIN`)
+    ///
+    /// ## Note
+    /// Many `WIN` combinations (for example `WIN + D` or `WIN + L`) are reserved by windows and
+    /// can't be registered by applications. Registering these hotkeys fails with an error.
+    ///
     Win,
 }
 
 impl ModKey {
-    /// Take in a string and interpret it as one of the modifier keys.
+    /// Get all modifier keys, in the order of their declaration.
+    ///
+    pub const fn all() -> [ModKey; 4] {
+        [ModKey::Alt, ModKey::Ctrl, ModKey::Shift, ModKey::Win]
+    }
+
+    /// Take in a string and interpret it as one of the modifier keys. The names are case
+    /// insensitive.
     /// Possible values are:
-    /// - ALT
+    /// - ALT / MENU
     /// - CTRL / CONTROL
     /// - SHIFT
-    /// - WIN / WINDOWS / SUPER
-    /// - NOREPEAT
+    /// - WIN / WINDOWS / SUPER / META
     ///
     pub fn from_keyname(val: &str) -> Result<Self, HkError> {
         Ok(match val.to_ascii_uppercase().as_ref() {
-            "ALT" => ModKey::Alt,
+            "ALT" | "MENU" => ModKey::Alt,
             "CTRL" | "CONTROL" => ModKey::Ctrl,
             "SHIFT" => ModKey::Shift,
-            "WIN" | "WINDOWS" | "SUPER" => ModKey::Win,
-            val => return Err(HkError::InvalidKey(val.to_string())),
+            "WIN" | "WINDOWS" | "SUPER" | "META" => ModKey::Win,
+            _ => return Err(HkError::InvalidModifier(val.to_string())),
         })
     }
 
     /// Obtain the modifier code for the `ModKey`.
     ///
     /// See: `fsModifiers` from <https://learn.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-registerhotkey>
-    /// 
+    ///
     pub const fn to_mod_code(&self) -> u32 {
-        use winapi::um::winuser::*;
+        use crate::keys::winapi_keycodes::*;
 
         match self {
             ModKey::Alt => MOD_ALT as u32,
@@ -48,25 +68,76 @@ impl ModKey {
         }
     }
 
-    /// Combine multiple `ModKey`s using bitwise OR
-    /// 
-    pub(crate) fn combine(keys: &[ModKey]) -> u32 {
-        keys.iter().fold(0, |a, b| a | b.to_mod_code())
+    /// Get the key of the left modifier key, for example `VKey::LControl` for `ModKey::Ctrl`.
+    ///
+    pub const fn left_key(&self) -> VKey {
+        match self {
+            ModKey::Alt => VKey::LMenu,
+            ModKey::Ctrl => VKey::LControl,
+            ModKey::Shift => VKey::LShift,
+            ModKey::Win => VKey::LWin,
+        }
+    }
+
+    /// Get the key of the right modifier key, for example `VKey::RControl` for `ModKey::Ctrl`.
+    ///
+    pub const fn right_key(&self) -> VKey {
+        match self {
+            ModKey::Alt => VKey::RMenu,
+            ModKey::Ctrl => VKey::RControl,
+            ModKey::Shift => VKey::RShift,
+            ModKey::Win => VKey::RWin,
+        }
+    }
+
+    /// Combine multiple `ModKey`s using bitwise OR. Duplicate modifier keys have no effect.
+    ///
+    pub fn combine(keys: &[ModKey]) -> u32 {
+        Self::combine_set(keys)
+    }
+
+    /// Same as `ModKey::combine`, but takes any collection of `ModKey`s, for example a
+    /// `HashSet<ModKey>` or `BTreeSet<ModKey>`.
+    ///
+    pub fn combine_set<'a>(keys: impl IntoIterator<Item = &'a ModKey>) -> u32 {
+        keys.into_iter().fold(0, |a, b| a | b.to_mod_code())
+    }
+
+    /// Split combined modifier codes into the individual `ModKey`s, in the order of their
+    /// declaration. This is the inverse of `ModKey::combine`, for example to decode the low word
+    /// of the `lParam` of a `WM_HOTKEY` message. Bits that don't belong to a modifier key, like
+    /// `MOD_NOREPEAT`, are ignored.
+    ///
+    pub fn from_bits(bits: u32) -> Vec<ModKey> {
+        Self::all()
+            .into_iter()
+            .filter(|modifier| bits & modifier.to_mod_code() != 0)
+            .collect()
     }
 }
 
 impl Display for ModKey {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let key = match self {
-            ModKey::Alt => "ALT",
-            ModKey::Ctrl => "CONTROL",
-            ModKey::Shift => "SHIFT",
-            ModKey::Win => "WIN",
+            ModKey::Alt => "Alt",
+            ModKey::Ctrl => "Ctrl",
+            ModKey::Shift => "Shift",
+            ModKey::Win => "Win",
         };
         write!(f, "{}", key)
     }
 }
 
+impl FromStr for ModKey {
+    type Err = HkError;
+
+    /// Parse a `ModKey` from a modifier name. See `ModKey::from_keyname` for the accepted names.
+    ///
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_keyname(s)
+    }
+}
+
 impl From<ModKey> for VKey {
     fn from(mk: ModKey) -> VKey {
         match mk {
@@ -77,3 +148,22 @@ impl From<ModKey> for VKey {
         }
     }
 }
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for ModKey {
+    /// Serialize the `ModKey` as its modifier name (see `Display`)
+    ///
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for ModKey {
+    /// Deserialize the `ModKey` from its modifier name (see `FromStr`)
+    ///
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        name.parse().map_err(serde::de::Error::custom)
+    }
+}
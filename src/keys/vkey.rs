@@ -1,9 +1,14 @@
-use std::{fmt::Display, hash::Hash};
+use std::{fmt::Display, hash::Hash, str::FromStr};
 
 use crate::error::HkError;
 
 use super::ModKey;
 
+#[cfg(all(windows, not(feature = "windows-sys")))]
+use winapi::um::winuser::VkKeyScanW;
+#[cfg(all(windows, feature = "windows-sys"))]
+use windows_sys::Win32::UI::Input::KeyboardAndMouse::VkKeyScanW;
+
 /// Virtual Key Code wrapper. The codes and variants follow the virtual key codes.
 /// Not supported as enum variants are the mouse buttons, IME keys, `VK_PACKET` and `VK_NONAME`.
 /// The letter keys (`A` to `Z`) are added as additionall variants, as well as the number keys
@@ -11,12 +16,18 @@ use super::ModKey;
 ///
 /// A `VKey` can be created for any arbitrary keycode by using the `CustomKeyCode` variant.
 ///
+/// The variants can be used directly in `const` contexts, for example `VKey::A`, `VKey::F1` or
+/// `VKey::Space`. The `const fn` constructors `from_char`, `from_digit` and `from_function_key`
+/// create the letter, number and function keys from their char or number.
+///
 /// See: <https://learn.microsoft.com/en-us/windows/win32/inputdev/virtual-key-codes>
 ///
 /// ## Note
 /// Matching against a `VKey` can be problematic since all of the variants can also be represented
 /// using the `CustomKeyCode` variant. If a reliable check for a `VKey` is needed, the keycode
-/// from the `VKey::to_vk_code` function should be used to get the unique keycode.
+/// from the `VKey::to_vk_code` function should be used to get the unique keycode, or the `VKey`
+/// can be turned into its named variant with `VKey::to_named` first. Comparing `VKey`s with `==`
+/// or ordering them also uses the keycodes, so both representations are equal.
 ///
 #[derive(Debug, Clone, Copy)]
 pub enum VKey {
@@ -138,6 +149,12 @@ pub enum VKey {
     /// F12 key
     F12,
     /// F13 key
+    ///
+    /// ## Note
+    /// The keys F13 to F24 are missing on most keyboards, but macro keyboards and key remapping
+    /// tools often send them. Since no application uses them by default, they make good hotkeys
+    /// without any modifiers.
+    ///
     F13,
     /// F14 key
     F14,
@@ -344,20 +361,254 @@ pub enum VKey {
 }
 
 impl VKey {
+    /// All named `VKey` variants, in the order of their declaration. `CustomKeyCode` is not
+    /// included.
+    ///
+    pub const ALL: &'static [VKey] = &[
+        VKey::Back,
+        VKey::Tab,
+        VKey::Clear,
+        VKey::Return,
+        VKey::Shift,
+        VKey::Control,
+        VKey::Menu,
+        VKey::Pause,
+        VKey::Capital,
+        VKey::Escape,
+        VKey::Space,
+        VKey::Prior,
+        VKey::Next,
+        VKey::End,
+        VKey::Home,
+        VKey::Left,
+        VKey::Up,
+        VKey::Right,
+        VKey::Down,
+        VKey::Select,
+        VKey::Print,
+        VKey::Execute,
+        VKey::Snapshot,
+        VKey::Insert,
+        VKey::Delete,
+        VKey::Help,
+        VKey::LWin,
+        VKey::RWin,
+        VKey::Apps,
+        VKey::Sleep,
+        VKey::Numpad0,
+        VKey::Numpad1,
+        VKey::Numpad2,
+        VKey::Numpad3,
+        VKey::Numpad4,
+        VKey::Numpad5,
+        VKey::Numpad6,
+        VKey::Numpad7,
+        VKey::Numpad8,
+        VKey::Numpad9,
+        VKey::Multiply,
+        VKey::Add,
+        VKey::Separator,
+        VKey::Subtract,
+        VKey::Decimal,
+        VKey::Divide,
+        VKey::F1,
+        VKey::F2,
+        VKey::F3,
+        VKey::F4,
+        VKey::F5,
+        VKey::F6,
+        VKey::F7,
+        VKey::F8,
+        VKey::F9,
+        VKey::F10,
+        VKey::F11,
+        VKey::F12,
+        VKey::F13,
+        VKey::F14,
+        VKey::F15,
+        VKey::F16,
+        VKey::F17,
+        VKey::F18,
+        VKey::F19,
+        VKey::F20,
+        VKey::F21,
+        VKey::F22,
+        VKey::F23,
+        VKey::F24,
+        VKey::Numlock,
+        VKey::Scroll,
+        VKey::LShift,
+        VKey::RShift,
+        VKey::LControl,
+        VKey::RControl,
+        VKey::LMenu,
+        VKey::RMenu,
+        VKey::BrowserBack,
+        VKey::BrowserForward,
+        VKey::BrowserRefresh,
+        VKey::BrowserStop,
+        VKey::BrowserSearch,
+        VKey::BrowserFavorites,
+        VKey::BrowserHome,
+        VKey::VolumeMute,
+        VKey::VolumeDown,
+        VKey::VolumeUp,
+        VKey::MediaNextTrack,
+        VKey::MediaPrevTrack,
+        VKey::MediaStop,
+        VKey::MediaPlayPause,
+        VKey::LaunchMail,
+        VKey::LaunchMediaSelect,
+        VKey::LaunchApp1,
+        VKey::LaunchApp2,
+        VKey::Oem1,
+        VKey::OemPlus,
+        VKey::OemComma,
+        VKey::OemMinus,
+        VKey::OemPeriod,
+        VKey::Oem2,
+        VKey::Oem3,
+        VKey::Oem4,
+        VKey::Oem5,
+        VKey::Oem6,
+        VKey::Oem7,
+        VKey::Oem8,
+        VKey::Oem102,
+        VKey::Attn,
+        VKey::Crsel,
+        VKey::Exsel,
+        VKey::Play,
+        VKey::Zoom,
+        VKey::Pa1,
+        VKey::OemClear,
+        VKey::Vk0,
+        VKey::Vk1,
+        VKey::Vk2,
+        VKey::Vk3,
+        VKey::Vk4,
+        VKey::Vk5,
+        VKey::Vk6,
+        VKey::Vk7,
+        VKey::Vk8,
+        VKey::Vk9,
+        VKey::A,
+        VKey::B,
+        VKey::C,
+        VKey::D,
+        VKey::E,
+        VKey::F,
+        VKey::G,
+        VKey::H,
+        VKey::I,
+        VKey::J,
+        VKey::K,
+        VKey::L,
+        VKey::M,
+        VKey::N,
+        VKey::O,
+        VKey::P,
+        VKey::Q,
+        VKey::R,
+        VKey::S,
+        VKey::T,
+        VKey::U,
+        VKey::V,
+        VKey::W,
+        VKey::X,
+        VKey::Y,
+        VKey::Z,
+    ];
+
+    /// Iterate over all named `VKey` variants, see `VKey::ALL`. Together with the `Display`
+    /// implementation this can be used to list the bindable keys, for example in a settings
+    /// screen.
+    ///
+    pub fn all() -> impl Iterator<Item = VKey> {
+        Self::ALL.iter().copied()
+    }
+
     /// Try to create a VKey from a char. This only works for the simple number and letter keys
-    /// ('A' to 'Z' and '0' to '9'). Letters can be upper or lower case
+    /// ('A' to 'Z' and '0' to '9'). Letters can be upper or lower case. The named variants are
+    /// returned, so `VKey::from_char('a')` matches the `VKey::A` pattern.
     ///
     pub const fn from_char(ch: char) -> Result<Self, HkError> {
         match ch.to_ascii_uppercase() {
-            ch @ ('A'..='Z' | '0'..='9') => Ok(Self::CustomKeyCode(ch as i32)),
+            ch @ ('A'..='Z' | '0'..='9') => Ok(Self::CustomKeyCode(ch as i32).to_named()),
             ch => Err(HkError::InvalidKeyChar(ch)),
         }
     }
 
+    /// Get the number key (`Vk0` to `Vk9`) for the given digit. Returns `None` for numbers above
+    /// 9.
+    ///
+    pub const fn from_digit(digit: u8) -> Option<Self> {
+        match digit {
+            0..=9 => Some(Self::CustomKeyCode('0' as i32 + digit as i32).to_named()),
+            _ => None,
+        }
+    }
+
+    /// Get the function key (`F1` to `F24`) with the given number. Returns `None` for 0 and
+    /// numbers above 24.
+    ///
+    pub const fn from_function_key(number: u8) -> Option<Self> {
+        use crate::keys::winapi_keycodes::VK_F1;
+
+        match number {
+            1..=24 => Some(Self::CustomKeyCode(VK_F1 + number as i32 - 1).to_named()),
+            _ => None,
+        }
+    }
+
+    /// Get the named variant for the keycode of this `VKey`, so that it can be matched against
+    /// the variants. A `CustomKeyCode` without a named variant is returned unchanged.
+    ///
+    pub const fn to_named(self) -> Self {
+        let vk_code = self.to_vk_code();
+
+        let mut i = 0;
+        while i < Self::ALL.len() {
+            if Self::ALL[i].to_vk_code() == vk_code {
+                return Self::ALL[i];
+            }
+            i += 1;
+        }
+
+        self
+    }
+
+    /// Get the key that types the given char on the current keyboard layout, for example
+    /// `VKey::Oem2` for '/' on a US layout. Unlike `VKey::from_char`, this works for punctuation
+    /// and other layout specific characters.
+    ///
+    /// Only the key itself is returned, the modifiers that are needed to type the char (like
+    /// SHIFT for uppercase letters) are ignored. The result depends on the keyboard layout of the
+    /// current thread, so the same char can map to different keys for different users. Returns
+    /// `None` if the char can't be typed on the current layout. On other platforms than windows,
+    /// `None` is always returned.
+    ///
+    /// ## Windows API Functions used
+    /// - <https://learn.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-vkkeyscanw>
+    ///
+    pub fn from_layout_char(ch: char) -> Option<Self> {
+        // VkKeyScanW only takes a single UTF-16 code unit
+        let ch = u16::try_from(u32::from(ch)).ok()?;
+
+        // The low byte contains the keycode and the high byte the shift state, both are -1 if
+        // there is no key for the char
+        let key_scan = unsafe { VkKeyScanW(ch) };
+        if key_scan == -1 {
+            return None;
+        }
+
+        let vk_code = (key_scan & 0xFF) as i32;
+        Some(Self::from_vk_code(vk_code).unwrap_or(Self::CustomKeyCode(vk_code)))
+    }
+
     /// Get the actual windows virtual keycode for the `VKey` for usage with winapi functions
     ///
     pub const fn to_vk_code(&self) -> i32 {
-        use winapi::um::winuser::*;
+        use crate::keys::winapi_keycodes::*;
         match self {
             VKey::Back => VK_BACK,
             VKey::Tab => VK_TAB,
@@ -517,53 +768,232 @@ impl VKey {
         }
     }
 
+    /// Try to create a `VKey` from an actual windows virtual keycode. This is the inverse of
+    /// `VKey::to_vk_code` for all keycodes that are covered by a named `VKey` variant. Keycodes
+    /// without a named variant are rejected with `HkError::InvalidKey`, use
+    /// `VKey::CustomKeyCode` to create a `VKey` for those.
+    ///
+    /// See: <https://learn.microsoft.com/en-us/windows/win32/inputdev/virtual-key-codes>
+    ///
+    pub fn from_vk_code(vk_code: i32) -> Result<Self, HkError> {
+        use crate::keys::winapi_keycodes::*;
+
+        Ok(match vk_code {
+            VK_BACK => VKey::Back,
+            VK_TAB => VKey::Tab,
+            VK_CLEAR => VKey::Clear,
+            VK_RETURN => VKey::Return,
+            VK_SHIFT => VKey::Shift,
+            VK_CONTROL => VKey::Control,
+            VK_MENU => VKey::Menu,
+            VK_PAUSE => VKey::Pause,
+            VK_CAPITAL => VKey::Capital,
+            VK_ESCAPE => VKey::Escape,
+            VK_SPACE => VKey::Space,
+            VK_PRIOR => VKey::Prior,
+            VK_NEXT => VKey::Next,
+            VK_END => VKey::End,
+            VK_HOME => VKey::Home,
+            VK_LEFT => VKey::Left,
+            VK_UP => VKey::Up,
+            VK_RIGHT => VKey::Right,
+            VK_DOWN => VKey::Down,
+            VK_SELECT => VKey::Select,
+            VK_PRINT => VKey::Print,
+            VK_EXECUTE => VKey::Execute,
+            VK_SNAPSHOT => VKey::Snapshot,
+            VK_INSERT => VKey::Insert,
+            VK_DELETE => VKey::Delete,
+            VK_HELP => VKey::Help,
+            VK_LWIN => VKey::LWin,
+            VK_RWIN => VKey::RWin,
+            VK_APPS => VKey::Apps,
+            VK_SLEEP => VKey::Sleep,
+            VK_NUMPAD0 => VKey::Numpad0,
+            VK_NUMPAD1 => VKey::Numpad1,
+            VK_NUMPAD2 => VKey::Numpad2,
+            VK_NUMPAD3 => VKey::Numpad3,
+            VK_NUMPAD4 => VKey::Numpad4,
+            VK_NUMPAD5 => VKey::Numpad5,
+            VK_NUMPAD6 => VKey::Numpad6,
+            VK_NUMPAD7 => VKey::Numpad7,
+            VK_NUMPAD8 => VKey::Numpad8,
+            VK_NUMPAD9 => VKey::Numpad9,
+            VK_MULTIPLY => VKey::Multiply,
+            VK_ADD => VKey::Add,
+            VK_SEPARATOR => VKey::Separator,
+            VK_SUBTRACT => VKey::Subtract,
+            VK_DECIMAL => VKey::Decimal,
+            VK_DIVIDE => VKey::Divide,
+            VK_F1 => VKey::F1,
+            VK_F2 => VKey::F2,
+            VK_F3 => VKey::F3,
+            VK_F4 => VKey::F4,
+            VK_F5 => VKey::F5,
+            VK_F6 => VKey::F6,
+            VK_F7 => VKey::F7,
+            VK_F8 => VKey::F8,
+            VK_F9 => VKey::F9,
+            VK_F10 => VKey::F10,
+            VK_F11 => VKey::F11,
+            VK_F12 => VKey::F12,
+            VK_F13 => VKey::F13,
+            VK_F14 => VKey::F14,
+            VK_F15 => VKey::F15,
+            VK_F16 => VKey::F16,
+            VK_F17 => VKey::F17,
+            VK_F18 => VKey::F18,
+            VK_F19 => VKey::F19,
+            VK_F20 => VKey::F20,
+            VK_F21 => VKey::F21,
+            VK_F22 => VKey::F22,
+            VK_F23 => VKey::F23,
+            VK_F24 => VKey::F24,
+            VK_NUMLOCK => VKey::Numlock,
+            VK_SCROLL => VKey::Scroll,
+            VK_LSHIFT => VKey::LShift,
+            VK_RSHIFT => VKey::RShift,
+            VK_LCONTROL => VKey::LControl,
+            VK_RCONTROL => VKey::RControl,
+            VK_LMENU => VKey::LMenu,
+            VK_RMENU => VKey::RMenu,
+            VK_BROWSER_BACK => VKey::BrowserBack,
+            VK_BROWSER_FORWARD => VKey::BrowserForward,
+            VK_BROWSER_REFRESH => VKey::BrowserRefresh,
+            VK_BROWSER_STOP => VKey::BrowserStop,
+            VK_BROWSER_SEARCH => VKey::BrowserSearch,
+            VK_BROWSER_FAVORITES => VKey::BrowserFavorites,
+            VK_BROWSER_HOME => VKey::BrowserHome,
+            VK_VOLUME_MUTE => VKey::VolumeMute,
+            VK_VOLUME_DOWN => VKey::VolumeDown,
+            VK_VOLUME_UP => VKey::VolumeUp,
+            VK_MEDIA_NEXT_TRACK => VKey::MediaNextTrack,
+            VK_MEDIA_PREV_TRACK => VKey::MediaPrevTrack,
+            VK_MEDIA_STOP => VKey::MediaStop,
+            VK_MEDIA_PLAY_PAUSE => VKey::MediaPlayPause,
+            VK_LAUNCH_MAIL => VKey::LaunchMail,
+            VK_LAUNCH_MEDIA_SELECT => VKey::LaunchMediaSelect,
+            VK_LAUNCH_APP1 => VKey::LaunchApp1,
+            VK_LAUNCH_APP2 => VKey::LaunchApp2,
+            VK_OEM_1 => VKey::Oem1,
+            VK_OEM_PLUS => VKey::OemPlus,
+            VK_OEM_COMMA => VKey::OemComma,
+            VK_OEM_MINUS => VKey::OemMinus,
+            VK_OEM_PERIOD => VKey::OemPeriod,
+            VK_OEM_2 => VKey::Oem2,
+            VK_OEM_3 => VKey::Oem3,
+            VK_OEM_4 => VKey::Oem4,
+            VK_OEM_5 => VKey::Oem5,
+            VK_OEM_6 => VKey::Oem6,
+            VK_OEM_7 => VKey::Oem7,
+            VK_OEM_8 => VKey::Oem8,
+            VK_OEM_102 => VKey::Oem102,
+            VK_ATTN => VKey::Attn,
+            VK_CRSEL => VKey::Crsel,
+            VK_EXSEL => VKey::Exsel,
+            VK_PLAY => VKey::Play,
+            VK_ZOOM => VKey::Zoom,
+            VK_PA1 => VKey::Pa1,
+            VK_OEM_CLEAR => VKey::OemClear,
+
+            // '0' to '9'
+            0x30 => VKey::Vk0,
+            0x31 => VKey::Vk1,
+            0x32 => VKey::Vk2,
+            0x33 => VKey::Vk3,
+            0x34 => VKey::Vk4,
+            0x35 => VKey::Vk5,
+            0x36 => VKey::Vk6,
+            0x37 => VKey::Vk7,
+            0x38 => VKey::Vk8,
+            0x39 => VKey::Vk9,
+            // 'A' to 'Z'
+            0x41 => VKey::A,
+            0x42 => VKey::B,
+            0x43 => VKey::C,
+            0x44 => VKey::D,
+            0x45 => VKey::E,
+            0x46 => VKey::F,
+            0x47 => VKey::G,
+            0x48 => VKey::H,
+            0x49 => VKey::I,
+            0x4A => VKey::J,
+            0x4B => VKey::K,
+            0x4C => VKey::L,
+            0x4D => VKey::M,
+            0x4E => VKey::N,
+            0x4F => VKey::O,
+            0x50 => VKey::P,
+            0x51 => VKey::Q,
+            0x52 => VKey::R,
+            0x53 => VKey::S,
+            0x54 => VKey::T,
+            0x55 => VKey::U,
+            0x56 => VKey::V,
+            0x57 => VKey::W,
+            0x58 => VKey::X,
+            0x59 => VKey::Y,
+            0x5A => VKey::Z,
+
+            _ => return Err(HkError::InvalidKey(format!("0x{:02X}", vk_code))),
+        })
+    }
+
     /// Take in a string and try to guess what Virtual Key (VK) it is meant to represent.
     /// Returns the VK code as i32 on success (a key representation was recognized).
     ///
     /// - For single character strings the ASCII code is used as VK, this is used to represent
-    /// alphanumeric keys
+    ///   alphanumeric keys
     /// - Many of the most common VKs are represented by their constant name. For example
-    /// VK_SPACE => spacebar key
-    /// - Any other key can be represented by directly specifying the VK keycode value in 2
-    /// digit hex representation. For example 0x08 == VK_TAB (Tab key)
+    ///   VK_SPACE => spacebar key
+    /// - Any other key can be represented by directly specifying the VK keycode value in hex
+    ///   representation, optionally prefixed with `VK_`. For example 0x08 == VK_0x08 == VK_TAB
+    ///   (Tab key)
+    /// - A few common aliases are accepted as well: ENTER, ESC, BACKSPACE, CAPSLOCK, PAGEUP,
+    ///   PAGEDOWN, PRINTSCREEN and SCROLLLOCK
+    /// - The names of the `VKey` variants are accepted as well. For example `MediaPlayPause` for
+    ///   VK_MEDIA_PLAY_PAUSE
+    ///
+    /// The key names are case insensitive.
     ///
     /// See <https://docs.microsoft.com/en-us/windows/win32/inputdev/virtual-key-codes>
     ///
-    pub fn from_keyname(val: &str) -> Result<Self, HkError> {
-        let val = val.to_ascii_uppercase();
+    pub fn from_keyname(keyname: &str) -> Result<Self, HkError> {
+        let val = keyname.to_ascii_uppercase();
 
         // Single letter => Simply use the ASCII Code
-        if val.as_bytes().len() == 1 {
+        if val.len() == 1 {
             let val = val.as_bytes()[0];
             if val.is_ascii_uppercase() || val.is_ascii_digit() {
                 return Ok(Self::CustomKeyCode(val as i32));
             }
         }
 
-        // 1 byte hex code => Use the raw keycode value
-        if val.len() >= 3 && val.len() <= 6 && val.starts_with("0x") || val.starts_with("0X") {
-            if let Ok(val) = i32::from_str_radix(&val[2..], 16) {
-                return Ok(Self::CustomKeyCode(val));
-            } else {
-                return Err(HkError::InvalidKey(val));
-            }
+        // Hex code => Use the raw keycode value. Up to 8 digits are accepted, so any keycode
+        // including negative ones (as their two's complement) can be represented
+        let val = val.trim_start_matches("VK_");
+        if let Some(hex) = val.strip_prefix("0X") {
+            return match u32::from_str_radix(hex, 16) {
+                Ok(code) if hex.len() <= 8 => Ok(Self::CustomKeyCode(code as i32)),
+                _ => Err(HkError::InvalidKey(keyname.to_string())),
+            };
         }
 
         // Try to match against hardcoded VK_* Key specifiers
-        Ok(match val.trim_start_matches("VK_") {
-            "BACK" => Self::Back,
+        Ok(match val {
+            "BACK" | "BACKSPACE" => Self::Back,
             "TAB" => Self::Tab,
             "CLEAR" => Self::Clear,
-            "RETURN" => Self::Return,
+            "RETURN" | "ENTER" => Self::Return,
             "SHIFT" => Self::Shift,
             "CONTROL" => Self::Control,
             "MENU" => Self::Menu,
             "PAUSE" => Self::Pause,
-            "CAPITAL" => Self::Capital,
-            "ESCAPE" => Self::Escape,
+            "CAPITAL" | "CAPSLOCK" => Self::Capital,
+            "ESCAPE" | "ESC" => Self::Escape,
             "SPACE" => Self::Space,
-            "PRIOR" => Self::Prior,
-            "NEXT" => Self::Next,
+            "PRIOR" | "PAGEUP" => Self::Prior,
+            "NEXT" | "PAGEDOWN" => Self::Next,
             "END" => Self::End,
             "HOME" => Self::Home,
             "LEFT" => Self::Left,
@@ -573,7 +1003,7 @@ impl VKey {
             "SELECT" => Self::Select,
             "PRINT" => Self::Print,
             "EXECUTE" => Self::Execute,
-            "SNAPSHOT" => Self::Snapshot,
+            "SNAPSHOT" | "PRINTSCREEN" => Self::Snapshot,
             "INSERT" => Self::Insert,
             "DELETE" => Self::Delete,
             "HELP" => Self::Help,
@@ -622,7 +1052,7 @@ impl VKey {
             "F23" => Self::F23,
             "F24" => Self::F24,
             "NUMLOCK" => Self::Numlock,
-            "SCROLL" => Self::Scroll,
+            "SCROLL" | "SCROLLLOCK" => Self::Scroll,
             "LSHIFT" => Self::LShift,
             "RSHIFT" => Self::RShift,
             "LCONTROL" => Self::LControl,
@@ -668,146 +1098,66 @@ impl VKey {
             "PA1" => Self::Pa1,
             "OEM_CLEAR" => Self::OemClear,
 
-            _ => return Err(HkError::InvalidKey(val)),
+            // The variant names are accepted as well, for example `MediaPlayPause`
+            _ => Self::ALL
+                .iter()
+                .copied()
+                .find(|vk| format!("{:?}", vk).eq_ignore_ascii_case(keyname))
+                .ok_or_else(|| HkError::InvalidKey(keyname.to_string()))?,
         })
     }
 }
 
+/// Display the canonical name of the `VKey`. Letter and number keys are displayed as the
+/// character (`A`, `5`) and a few keys use their common name (`Enter`, `Backspace`, `PageUp`,
+/// `CapsLock`). All other named keys are displayed as their variant name (`F1`, `Space`,
+/// `MediaPlayPause`).
+///
+/// The generic `Shift`, `Control` and `Menu` keys are displayed as their `VK_*` constant names
+/// (`VK_SHIFT`), since their variant names are parsed as modifier keys in key combinations. Keys
+/// without a variant are displayed as their hex keycode (`VK_0x0E`).
+///
+/// The displayed names can be parsed back into the same `VKey` using `VKey::from_keyname` or
+/// `FromStr`.
+///
 impl Display for VKey {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        use winapi::um::winuser::*;
-
-        let code = self.to_vk_code();
-
-        if code >= 'A' as i32 && code <= 'Z' as i32 {
-            return write!(f, "{}", code as u8 as char);
-        }
+        let key = self.to_named();
+        let code = key.to_vk_code();
 
-        if code >= '0' as i32 && code <= '9' as i32 {
+        if (b'0' as i32..=b'9' as i32).contains(&code) {
             return write!(f, "{}", code as u8 as char);
         }
 
-        let val = match code {
-            VK_BACK => "VK_BACK",
-            VK_TAB => "VK_TAB",
-            VK_CLEAR => "VK_CLEAR",
-            VK_RETURN => "VK_RETURN",
-            VK_SHIFT => "VK_SHIFT",
-            VK_CONTROL => "VK_CONTROL",
-            VK_MENU => "VK_MENU",
-            VK_PAUSE => "VK_PAUSE",
-            VK_CAPITAL => "VK_CAPITAL",
-            VK_ESCAPE => "VK_ESCAPE",
-            VK_SPACE => "VK_SPACE",
-            VK_PRIOR => "VK_PRIOR",
-            VK_NEXT => "VK_NEXT",
-            VK_END => "VK_END",
-            VK_HOME => "VK_HOME",
-            VK_LEFT => "VK_LEFT",
-            VK_UP => "VK_UP",
-            VK_RIGHT => "VK_RIGHT",
-            VK_DOWN => "VK_DOWN",
-            VK_SELECT => "VK_SELECT",
-            VK_PRINT => "VK_PRINT",
-            VK_EXECUTE => "VK_EXECUTE",
-            VK_SNAPSHOT => "VK_SNAPSHOT",
-            VK_INSERT => "VK_INSERT",
-            VK_DELETE => "VK_DELETE",
-            VK_HELP => "VK_HELP",
-            VK_LWIN => "VK_LWIN",
-            VK_RWIN => "VK_RWIN",
-            VK_APPS => "VK_APPS",
-            VK_SLEEP => "VK_SLEEP",
-            VK_NUMPAD0 => "VK_NUMPAD0",
-            VK_NUMPAD1 => "VK_NUMPAD1",
-            VK_NUMPAD2 => "VK_NUMPAD2",
-            VK_NUMPAD3 => "VK_NUMPAD3",
-            VK_NUMPAD4 => "VK_NUMPAD4",
-            VK_NUMPAD5 => "VK_NUMPAD5",
-            VK_NUMPAD6 => "VK_NUMPAD6",
-            VK_NUMPAD7 => "VK_NUMPAD7",
-            VK_NUMPAD8 => "VK_NUMPAD8",
-            VK_NUMPAD9 => "VK_NUMPAD9",
-            VK_MULTIPLY => "VK_MULTIPLY",
-            VK_ADD => "VK_ADD",
-            VK_SEPARATOR => "VK_SEPARATOR",
-            VK_SUBTRACT => "VK_SUBTRACT",
-            VK_DECIMAL => "VK_DECIMAL",
-            VK_DIVIDE => "VK_DIVIDE",
-            VK_F1 => "VK_F1",
-            VK_F2 => "VK_F2",
-            VK_F3 => "VK_F3",
-            VK_F4 => "VK_F4",
-            VK_F5 => "VK_F5",
-            VK_F6 => "VK_F6",
-            VK_F7 => "VK_F7",
-            VK_F8 => "VK_F8",
-            VK_F9 => "VK_F9",
-            VK_F10 => "VK_F10",
-            VK_F11 => "VK_F11",
-            VK_F12 => "VK_F12",
-            VK_F13 => "VK_F13",
-            VK_F14 => "VK_F14",
-            VK_F15 => "VK_F15",
-            VK_F16 => "VK_F16",
-            VK_F17 => "VK_F17",
-            VK_F18 => "VK_F18",
-            VK_F19 => "VK_F19",
-            VK_F20 => "VK_F20",
-            VK_F21 => "VK_F21",
-            VK_F22 => "VK_F22",
-            VK_F23 => "VK_F23",
-            VK_F24 => "VK_F24",
-            VK_NUMLOCK => "VK_NUMLOCK",
-            VK_SCROLL => "VK_SCROLL",
-            VK_LSHIFT => "VK_LSHIFT",
-            VK_RSHIFT => "VK_RSHIFT",
-            VK_LCONTROL => "VK_LCONTROL",
-            VK_RCONTROL => "VK_RCONTROL",
-            VK_LMENU => "VK_LMENU",
-            VK_RMENU => "VK_RMENU",
-            VK_BROWSER_BACK => "VK_BROWSER_BACK",
-            VK_BROWSER_FORWARD => "VK_BROWSER_FORWARD",
-            VK_BROWSER_REFRESH => "VK_BROWSER_REFRESH",
-            VK_BROWSER_STOP => "VK_BROWSER_STOP",
-            VK_BROWSER_SEARCH => "VK_BROWSER_SEARCH",
-            VK_BROWSER_FAVORITES => "VK_BROWSER_FAVORITES",
-            VK_BROWSER_HOME => "VK_BROWSER_HOME",
-            VK_VOLUME_MUTE => "VK_VOLUME_MUTE",
-            VK_VOLUME_DOWN => "VK_VOLUME_DOWN",
-            VK_VOLUME_UP => "VK_VOLUME_UP",
-            VK_MEDIA_NEXT_TRACK => "VK_MEDIA_NEXT_TRACK",
-            VK_MEDIA_PREV_TRACK => "VK_MEDIA_PREV_TRACK",
-            VK_MEDIA_STOP => "VK_MEDIA_STOP",
-            VK_MEDIA_PLAY_PAUSE => "VK_MEDIA_PLAY_PAUSE",
-            VK_LAUNCH_MAIL => "VK_LAUNCH_MAIL",
-            VK_LAUNCH_MEDIA_SELECT => "VK_LAUNCH_MEDIA_SELECT",
-            VK_LAUNCH_APP1 => "VK_LAUNCH_APP1",
-            VK_LAUNCH_APP2 => "VK_LAUNCH_APP2",
-            VK_OEM_1 => "VK_OEM_1",
-            VK_OEM_PLUS => "VK_OEM_PLUS",
-            VK_OEM_COMMA => "VK_OEM_COMMA",
-            VK_OEM_MINUS => "VK_OEM_MINUS",
-            VK_OEM_PERIOD => "VK_OEM_PERIOD",
-            VK_OEM_2 => "VK_OEM_2",
-            VK_OEM_3 => "VK_OEM_3",
-            VK_OEM_4 => "VK_OEM_4",
-            VK_OEM_5 => "VK_OEM_5",
-            VK_OEM_6 => "VK_OEM_6",
-            VK_OEM_7 => "VK_OEM_7",
-            VK_OEM_8 => "VK_OEM_8",
-            VK_OEM_102 => "VK_OEM_102",
-            VK_ATTN => "VK_ATTN",
-            VK_CRSEL => "VK_CRSEL",
-            VK_EXSEL => "VK_EXSEL",
-            VK_PLAY => "VK_PLAY",
-            VK_ZOOM => "VK_ZOOM",
-            VK_PA1 => "VK_PA1",
-            VK_OEM_CLEAR => "VK_OEM_CLEAR",
-            vk_code => return write!(f, "0x{:x}", vk_code),
+        let name = match key {
+            VKey::Back => "Backspace",
+            VKey::Return => "Enter",
+            VKey::Capital => "CapsLock",
+            VKey::Prior => "PageUp",
+            VKey::Next => "PageDown",
+            VKey::Snapshot => "PrintScreen",
+            VKey::Numlock => "NumLock",
+            VKey::Scroll => "ScrollLock",
+            VKey::Shift => "VK_SHIFT",
+            VKey::Control => "VK_CONTROL",
+            VKey::Menu => "VK_MENU",
+            // Negative keycodes are displayed as their two's complement, which is parsed back
+            // into the same keycode
+            VKey::CustomKeyCode(code) => return write!(f, "VK_0x{:02X}", code as u32),
+            key => return write!(f, "{:?}", key),
         };
 
-        write!(f, "{}", val)
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for VKey {
+    type Err = HkError;
+
+    /// Parse a `VKey` from a key name. See `VKey::from_keyname` for the accepted formats.
+    ///
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_keyname(s)
     }
 }
 
@@ -819,6 +1169,21 @@ impl PartialEq<VKey> for VKey {
 
 impl Eq for VKey {}
 
+/// `VKey`s are ordered by their keycode, consistent with `PartialEq`. This allows using them as
+/// keys of a `BTreeMap` for a deterministic order.
+///
+impl PartialOrd<VKey> for VKey {
+    fn partial_cmp(&self, other: &VKey) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for VKey {
+    fn cmp(&self, other: &VKey) -> std::cmp::Ordering {
+        self.to_vk_code().cmp(&other.to_vk_code())
+    }
+}
+
 impl Hash for VKey {
     fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
         self.to_vk_code().hash(state);
@@ -829,7 +1194,7 @@ impl TryInto<ModKey> for VKey {
     type Error = ();
 
     fn try_into(self) -> Result<ModKey, Self::Error> {
-        use winapi::um::winuser::*;
+        use crate::keys::winapi_keycodes::*;
 
         Ok(match self.to_vk_code() {
             VK_MENU | VK_LMENU | VK_RMENU => ModKey::Alt,
@@ -840,3 +1205,124 @@ impl TryInto<ModKey> for VKey {
         })
     }
 }
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for VKey {
+    /// Serialize the `VKey` as its key name (see `Display`)
+    ///
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for VKey {
+    /// Deserialize the `VKey` from its key name (see `FromStr`)
+    ///
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        name.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+/// Replacement for `VkKeyScanW` on other platforms than windows, no char can be typed
+///
+#[cfg(not(windows))]
+#[allow(non_snake_case)]
+unsafe fn VkKeyScanW(_ch: u16) -> i16 {
+    -1
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    #[test]
+    fn vk_codes_round_trip() {
+        for vk in VKey::all() {
+            assert_eq!(VKey::from_vk_code(vk.to_vk_code()), Ok(vk), "{:?}", vk);
+            assert_eq!(VKey::CustomKeyCode(vk.to_vk_code()).to_named(), vk);
+        }
+    }
+
+    #[test]
+    fn vk_codes_round_trip_for_numpad_oem_and_extended_function_keys() {
+        let keys = [
+            VKey::Numpad0,
+            VKey::Numpad9,
+            VKey::Multiply,
+            VKey::Add,
+            VKey::Separator,
+            VKey::Subtract,
+            VKey::Decimal,
+            VKey::Divide,
+            VKey::Oem1,
+            VKey::OemPlus,
+            VKey::OemComma,
+            VKey::OemMinus,
+            VKey::OemPeriod,
+            VKey::Oem102,
+            VKey::F13,
+            VKey::F24,
+        ];
+        for vk in keys {
+            assert!(VKey::ALL.contains(&vk), "{:?}", vk);
+            assert_eq!(VKey::from_vk_code(vk.to_vk_code()), Ok(vk), "{:?}", vk);
+        }
+
+        for number in 13..=24 {
+            let vk = VKey::from_function_key(number).unwrap();
+            assert_eq!(vk.to_string(), format!("F{}", number));
+        }
+    }
+
+    #[test]
+    fn all_contains_every_named_variant_once() {
+        assert_eq!(VKey::all().count(), 152);
+        assert_eq!(VKey::all().count(), VKey::ALL.len());
+
+        let vk_codes: HashSet<i32> = VKey::all().map(|vk| vk.to_vk_code()).collect();
+        assert_eq!(vk_codes.len(), VKey::ALL.len());
+        assert!(!VKey::all().any(|vk| matches!(vk, VKey::CustomKeyCode(_))));
+    }
+
+    #[test]
+    fn unnamed_vk_codes_are_rejected() {
+        assert!(VKey::from_vk_code(0x0E).is_err());
+        assert!(VKey::from_vk_code(-1).is_err());
+        assert_eq!(
+            VKey::CustomKeyCode(0x0E).to_named(),
+            VKey::CustomKeyCode(0x0E)
+        );
+    }
+
+    #[test]
+    fn display_round_trips_through_from_str() {
+        for vk in VKey::all() {
+            let name = vk.to_string();
+            assert_eq!(VKey::from_str(&name), Ok(vk), "{}", name);
+        }
+    }
+
+    #[test]
+    fn display_uses_canonical_names() {
+        assert_eq!(VKey::A.to_string(), "A");
+        assert_eq!(VKey::Vk5.to_string(), "5");
+        assert_eq!(VKey::F1.to_string(), "F1");
+        assert_eq!(VKey::Return.to_string(), "Enter");
+        assert_eq!(VKey::Space.to_string(), "Space");
+        assert_eq!(VKey::MediaPlayPause.to_string(), "MediaPlayPause");
+        assert_eq!(VKey::CustomKeyCode(0x41).to_string(), "A");
+        assert_eq!(VKey::CustomKeyCode(0x0E).to_string(), "VK_0x0E");
+    }
+
+    #[test]
+    fn custom_keycodes_round_trip() {
+        for code in [0x0E, 0xFF, 0x100, 0xFFFF, 0x1_0000, i32::MAX, -1, i32::MIN] {
+            let vk = VKey::CustomKeyCode(code);
+            assert_eq!(VKey::from_str(&vk.to_string()), Ok(vk), "{}", vk);
+        }
+    }
+}
@@ -1,53 +1,514 @@
-#[cfg(not(target_os = "windows"))]
-compile_error!("Only supported on windows");
+#[macro_use]
+mod logging;
 
-#[cfg(windows)]
+pub mod accel;
+pub mod backend;
+pub mod channeled;
+pub mod config;
 pub mod error;
-#[cfg(windows)]
+pub mod foreground;
 pub mod keys;
+#[cfg(feature = "mouse")]
+pub mod mouse;
+pub mod sequences;
 
-#[cfg(windows)]
+#[cfg(feature = "simulate")]
+pub mod simulate;
 pub mod singlethreaded;
-#[cfg(all(windows, feature = "threadsafe"))]
+#[cfg(feature = "tokio")]
+pub mod stream;
+#[cfg(feature = "threadsafe")]
 pub mod threadsafe;
 
-#[cfg(all(windows, feature = "threadsafe"))]
+#[cfg(feature = "threadsafe")]
 pub use threadsafe::HotkeyManager;
 
-#[cfg(all(windows, not(feature = "threadsafe")))]
+#[cfg(not(feature = "threadsafe"))]
 pub use singlethreaded::HotkeyManager;
 
-#[cfg(windows)]
-use winapi::shared::windef::HWND;
-#[cfg(windows)]
-use winapi::um::winuser::{GetAsyncKeyState, PostMessageW, WM_NULL};
+use std::{
+    cell::{Cell, RefCell},
+    collections::HashMap,
+    fmt,
+    iter::FusedIterator,
+    marker::PhantomData,
+    ops::{ControlFlow, Deref, DerefMut},
+    panic,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver, Sender},
+        Arc, Mutex, OnceLock,
+    },
+    thread,
+    time::{Duration, Instant},
+};
 
-#[cfg(windows)]
-use crate::{error::HkError, keys::*};
+#[cfg(all(windows, not(feature = "windows-sys")))]
+use winapi::um::winuser::{GetAsyncKeyState, GetKeyState};
+#[cfg(all(windows, feature = "windows-sys"))]
+use windows_sys::Win32::UI::Input::KeyboardAndMouse::{GetAsyncKeyState, GetKeyState};
+
+use crate::{backend::HotkeyTrigger, error::HkError, keys::*};
 
 /// Identifier of a registered hotkey. This is returned when registering a hotkey and can be used
 /// to unregister it later.
 ///
-#[cfg(windows)]
 #[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
 pub struct HotkeyId(i32);
 
+impl HotkeyId {
+    /// Get the raw id value that is used to identify the hotkey with the windows API.
+    ///
+    pub const fn as_i32(&self) -> i32 {
+        self.0
+    }
+
+    /// Create a `HotkeyId` from a raw id value, for example one that was previously obtained
+    /// from `HotkeyId::as_i32` and persisted.
+    ///
+    /// # Note
+    /// This only makes sense for ids that were previously handed out by the same `HotkeyManager`.
+    /// Any other value doesn't identify a hotkey registered by this crate, so passing it to
+    /// `unregister` will call `UnregisterHotKey` with a foreign id.
+    ///
+    pub const fn from_raw(id: i32) -> HotkeyId {
+        HotkeyId(id)
+    }
+}
+
+/// A stored hotkey callback that gets the context of the triggered hotkey
+type ContextCallback<T> = Box<dyn FnMut(&HotkeyContext) -> T + 'static>;
+
+/// A callback invocation that is executed on the async dispatch worker
+type AsyncJob = Box<dyn FnOnce() + Send + 'static>;
+
+/// Get the sender of the worker thread that executes the callbacks of all hotkeys registered with
+/// `register_async_dispatch`. The worker is started on first use and runs for the rest of the
+/// process.
+///
+fn async_worker() -> &'static Sender<AsyncJob> {
+    static WORKER: OnceLock<Sender<AsyncJob>> = OnceLock::new();
+
+    WORKER.get_or_init(|| {
+        let (job_snd, job_rec) = mpsc::channel::<AsyncJob>();
+        thread::Builder::new()
+            .name("hotkey-async-dispatch".to_string())
+            .spawn(move || {
+                for job in job_rec {
+                    // A panicking callback must not stop the callbacks of the other hotkeys
+                    let _ = panic::catch_unwind(panic::AssertUnwindSafe(job));
+                }
+            })
+            .expect("failed to spawn the async dispatch worker thread");
+        job_snd
+    })
+}
+
 /// HotkeyCallback contains the callback function and a list of extra_keys that need to be pressed
 /// together with the hotkey when executing the callback.
 ///
-#[cfg(windows)]
 struct HotkeyCallback<T> {
-    /// Callback function to execute  when the hotkey & extrakeys match
-    callback: Box<dyn Fn() -> T + 'static>,
+    /// Callback function to execute  when the hotkey & extrakeys match. The `RefCell` allows
+    /// calling `FnMut` callbacks while handling hotkeys through a shared reference
+    callback: RefCell<ContextCallback<T>>,
+    /// The main key of the hotkey
+    key: VKey,
+    /// The modifier keys of the hotkey
+    key_modifiers: Vec<ModKey>,
     /// List of additional VKeys that are required to be pressed to execute
     /// the callback
     extra_keys: Vec<VKey>,
+    /// The options that the hotkey was registered with
+    options: RegisterOptions,
+    /// Whether the hotkey is currently registered with windows. Disabled hotkeys keep their id
+    /// and callback, but are not triggered
+    enabled: bool,
+    /// The time of the last press that was not part of a double press, if the hotkey is
+    /// registered with `RegisterOptions::double_press`
+    last_press: Cell<Option<Instant>>,
+    /// The time the callback was last executed
+    last_fired: Cell<Option<Instant>>,
+    /// The number of times the callback was executed
+    fire_count: Cell<u64>,
+}
+
+impl<T> HotkeyCallback<T> {
+    /// Check if the hotkey was registered for the given key combination. The order of the
+    /// modifier keys doesn't matter.
+    ///
+    fn matches_combo(&self, key: VKey, key_modifiers: &[ModKey]) -> bool {
+        self.key == key && ModKey::combine(&self.key_modifiers) == ModKey::combine(key_modifiers)
+    }
+
+    /// Check if the key combination that triggered a hotkey event matches the hotkey. The main
+    /// key must always be the registered one, this catches events that don't belong to the id
+    /// they were sent for. With `RegisterOptions::strict_modifiers`, exactly the registered
+    /// modifiers must have been held down as well. Events that don't report their trigger always
+    /// match.
+    ///
+    fn matches_trigger(&self, trigger: Option<HotkeyTrigger>) -> bool {
+        trigger.is_none_or(|trigger| {
+            trigger.key() == self.key
+                && (!self.options.strict_modifiers
+                    || trigger.matches_modifiers(&self.key_modifiers))
+        })
+    }
+
+    /// Check if the callback should be executed for a received hotkey event and record the press.
+    /// The trigger needs to match, all extra keys need to be pressed, none of the forbidden keys
+    /// may be pressed, AltGr must not be held down with `RegisterOptions::exclude_altgr` and the
+    /// condition, if any, needs to return true. Only presses that match count towards a double
+    /// press and the minimum interval. The condition is only evaluated when the keys match.
+    ///
+    /// Returns the reason why the event is ignored otherwise, which is logged with the `log`
+    /// feature.
+    ///
+    fn accept_event(
+        &self,
+        trigger: Option<HotkeyTrigger>,
+        key_state: impl Fn(VKey) -> bool,
+        now: Instant,
+    ) -> Result<(), &'static str> {
+        if !self.matches_trigger(trigger) {
+            return Err("the reported key combination doesn't match");
+        }
+        if !self.extra_keys_match(&key_state) {
+            return Err("the extra keys are not pressed");
+        }
+        if self.options.forbidden_keys.iter().any(|vk| key_state(*vk)) {
+            return Err("a forbidden key is pressed");
+        }
+        if self.options.exclude_altgr && altgr_pressed(&key_state) {
+            return Err("AltGr is pressed");
+        }
+        let condition_met = self
+            .options
+            .condition
+            .as_ref()
+            .is_none_or(|condition| condition());
+        if !condition_met {
+            return Err("the condition is not met");
+        }
+        if !self.record_press(now) {
+            return Err("waiting for the second press");
+        }
+        if !self.record_fire(now) {
+            return Err("the minimum interval has not passed");
+        }
+
+        Ok(())
+    }
+
+    /// Check if the extra keys are pressed according to the `ExtraKeyMatch` mode of the hotkey.
+    /// A hotkey without extra keys always matches.
+    ///
+    fn extra_keys_match(&self, key_state: impl Fn(VKey) -> bool) -> bool {
+        if self.extra_keys.is_empty() {
+            return true;
+        }
+
+        let mut extra_keys = self.extra_keys.iter();
+        match self.options.extra_key_match {
+            ExtraKeyMatch::All => extra_keys.all(|vk| key_state(*vk)),
+            ExtraKeyMatch::Any => extra_keys.any(|vk| key_state(*vk)),
+        }
+    }
+
+    /// Record a matching press of the hotkey and check if the callback should be executed. For
+    /// hotkeys with `RegisterOptions::double_press`, this is only the case for the second press of
+    /// a double press. The presses are reset afterwards, so a third press starts a new double
+    /// press.
+    ///
+    fn record_press(&self, now: Instant) -> bool {
+        let Some(max_interval) = self.options.double_press else {
+            return true;
+        };
+
+        match self.last_press.get() {
+            Some(last) if now.duration_since(last) <= max_interval => {
+                self.last_press.set(None);
+                true
+            }
+            _ => {
+                self.last_press.set(Some(now));
+                false
+            }
+        }
+    }
+
+    /// Record that the callback is about to be executed and check if this is allowed by the
+    /// `RegisterOptions::min_interval` of the hotkey. Suppressed executions don't count as the
+    /// last execution and are not added to the fire count.
+    ///
+    fn record_fire(&self, now: Instant) -> bool {
+        let too_soon = match (self.options.min_interval, self.last_fired.get()) {
+            (Some(min_interval), Some(last)) => now.duration_since(last) < min_interval,
+            _ => false,
+        };
+        if too_soon {
+            return false;
+        }
+
+        self.last_fired.set(Some(now));
+        self.fire_count.set(self.fire_count.get() + 1);
+        true
+    }
+
+    /// Get the key combination that the hotkey was registered for.
+    ///
+    fn combination(&self) -> KeyCombination {
+        KeyCombination::new(self.key, &self.key_modifiers).with_extra_keys(&self.extra_keys)
+    }
+
+    /// Describe the hotkey for `HotkeyManagerImpl::dump_config`.
+    ///
+    fn descriptor(&self) -> config::BindingDescriptor {
+        config::BindingDescriptor {
+            combo: KeyCombination::new(self.key, &self.key_modifiers).to_string(),
+            extra: self.extra_keys.iter().map(VKey::to_string).collect(),
+            label: self.options.label.clone(),
+            enabled: self.enabled,
+        }
+    }
+
+    /// Get an owned description of the hotkey that was registered with the given id.
+    ///
+    fn info(&self, id: HotkeyId) -> HotkeyInfo {
+        HotkeyInfo {
+            id,
+            key: self.key,
+            modifiers: self.key_modifiers.clone(),
+            extra_keys: self.extra_keys.clone(),
+            label: self.options.label.clone(),
+            enabled: self.enabled,
+        }
+    }
+}
+
+/// Description of a hotkey that is registered in a `HotkeyManager`, as returned by
+/// `HotkeyManagerImpl::registered`.
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HotkeyInfo {
+    /// The id of the hotkey
+    pub id: HotkeyId,
+    /// The main key of the hotkey
+    pub key: VKey,
+    /// The modifier keys of the hotkey
+    pub modifiers: Vec<ModKey>,
+    /// The additional keys that need to be pressed together with the hotkey
+    pub extra_keys: Vec<VKey>,
+    /// The human-readable label of the hotkey, see `RegisterOptions::label`
+    pub label: Option<String>,
+    /// Whether the hotkey is enabled, see `HotkeyManagerImpl::disable`
+    pub enabled: bool,
 }
 
-#[cfg(windows)]
+/// The hotkey that triggered a callback, passed to the callbacks that are registered with
+/// `HotkeyManagerImpl::register_ctx`.
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HotkeyContext {
+    /// The id of the hotkey that was triggered
+    pub id: HotkeyId,
+    /// The main key of the hotkey
+    pub key: VKey,
+    /// The modifier keys that were held down when the hotkey was triggered. If the backend
+    /// doesn't report them, these are the registered modifier keys of the hotkey
+    pub modifiers: Vec<ModKey>,
+}
+
+/// A hotkey event whose callback was executed, as returned by
+/// `HotkeyManagerImpl::handle_hotkey_detailed`.
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HotkeyEvent<T> {
+    /// The id of the hotkey that was triggered
+    pub id: HotkeyId,
+    /// The return value of the executed callback
+    pub result: T,
+    /// The time of the `WM_HOTKEY` message in milliseconds since the system was started. `None`
+    /// if the backend didn't report it
+    pub time: Option<u32>,
+    /// The cursor position in screen coordinates when the `WM_HOTKEY` message was posted. `None`
+    /// if the backend didn't report it
+    pub cursor_pos: Option<(i32, i32)>,
+}
+
+/// The return type of fallible callbacks, as used by `HotkeyManagerImpl::register_try` and
+/// `HotkeyManagerImpl::event_loop_try`. It is implemented for every `Result`, the success value
+/// is discarded by the event loop.
+///
+pub trait CallbackResult {
+    /// The error type of the callbacks. This is independent of `HkError`, which is only used for
+    /// the errors of the `HotkeyManager` itself.
+    type Error;
+
+    /// Get the error of the callback, if it failed.
+    ///
+    fn into_result(self) -> Result<(), Self::Error>;
+}
+
+impl<U, E> CallbackResult for Result<U, E> {
+    type Error = E;
+
+    fn into_result(self) -> Result<(), E> {
+        self.map(drop)
+    }
+}
+
+/// Determines which of the extra keys of a hotkey need to be pressed to execute its callback.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ExtraKeyMatch {
+    /// All extra keys need to be pressed
+    #[default]
+    All,
+    /// At least one of the extra keys needs to be pressed
+    Any,
+}
+
+/// Additional options for registering a hotkey using `HotkeyManagerImpl::register_extrakeys_opts`.
+/// The `Default` options are the same as the ones used by `HotkeyManagerImpl::register_extrakeys`.
+///
+#[derive(Clone, Default)]
+pub struct RegisterOptions {
+    /// Allow the hotkey to be triggered repeatedly by the keyboard auto-repeat while the keys are
+    /// held down. By default the hotkey is registered with `MOD_NOREPEAT`, so holding down the
+    /// keys only triggers the hotkey once.
+    pub allow_repeat: bool,
+    /// Which of the extra keys need to be pressed. By default all of them are required.
+    pub extra_key_match: ExtraKeyMatch,
+    /// Keys that must not be pressed when the hotkey is triggered. If any of them is pressed, the
+    /// hotkey event is ignored.
+    pub forbidden_keys: Vec<VKey>,
+    /// Only execute the callback if the condition returns true. The condition is evaluated every
+    /// time the hotkey is triggered, after checking the extra keys and forbidden keys.
+    pub condition: Option<Arc<dyn Fn() -> bool + Send + Sync>>,
+    /// Only execute the callback when the hotkey is pressed twice within the given interval.
+    /// Single presses are ignored.
+    pub double_press: Option<Duration>,
+    /// Ignore hotkey events that arrive sooner than the given interval after the last time the
+    /// callback was executed. Unlike `allow_repeat`, this also limits deliberate rapid presses.
+    pub min_interval: Option<Duration>,
+    /// A human-readable label for the hotkey, for example `"Toggle Overlay"` to display the
+    /// hotkeys in a settings screen. Labels don't need to be unique.
+    pub label: Option<String>,
+    /// Ignore hotkey events where other modifier keys than the registered ones were held down,
+    /// according to the modifiers reported with the `WM_HOTKEY` message. This keeps a tight
+    /// binding like `CTRL + A` from reacting to `CTRL + SHIFT + A`.
+    pub strict_modifiers: bool,
+    /// Ignore hotkey events while AltGr is held down (see `is_altgr_active`). Windows reports
+    /// AltGr as CTRL + ALT, so this keeps `CTRL + ALT` hotkeys from firing when the user types a
+    /// character with AltGr, like `@` or `€` on many european layouts.
+    pub exclude_altgr: bool,
+    /// Swallow the key events of the hotkey, so they don't reach the foreground application. See
+    /// `HotkeyManagerImpl::register_suppressing` for the constraints of suppressing hotkeys.
+    #[cfg(feature = "hook")]
+    pub suppress: bool,
+}
+
+impl fmt::Debug for RegisterOptions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut debug = f.debug_struct("RegisterOptions");
+        debug
+            .field("allow_repeat", &self.allow_repeat)
+            .field("extra_key_match", &self.extra_key_match)
+            .field("forbidden_keys", &self.forbidden_keys)
+            .field("condition", &self.condition.as_ref().map(|_| ".."))
+            .field("double_press", &self.double_press)
+            .field("min_interval", &self.min_interval)
+            .field("label", &self.label)
+            .field("strict_modifiers", &self.strict_modifiers)
+            .field("exclude_altgr", &self.exclude_altgr);
+        #[cfg(feature = "hook")]
+        debug.field("suppress", &self.suppress);
+        debug.finish()
+    }
+}
+
+/// A single hotkey binding for registering multiple hotkeys at once using
+/// `HotkeyManagerImpl::register_many`.
+///
+pub struct Binding<T> {
+    /// The main key of the hotkey
+    pub key: VKey,
+    /// The modifier keys of the hotkey
+    pub modifiers: Vec<ModKey>,
+    /// The additional keys that need to be pressed together with the hotkey
+    pub extra_keys: Vec<VKey>,
+    /// The options that the hotkey is registered with
+    pub options: RegisterOptions,
+    /// The callback that is executed when the hotkey is triggered
+    pub callback: Box<dyn FnMut() -> T + Send + 'static>,
+}
+
+impl<T> Binding<T> {
+    /// Create a new `Binding` without extra keys and with the default `RegisterOptions`.
+    ///
+    pub fn new(
+        key: VKey,
+        key_modifiers: &[ModKey],
+        callback: impl FnMut() -> T + Send + 'static,
+    ) -> Binding<T> {
+        Binding {
+            key,
+            modifiers: key_modifiers.to_vec(),
+            extra_keys: Vec::new(),
+            options: RegisterOptions::default(),
+            callback: Box::new(callback),
+        }
+    }
+
+    /// Set the extra keys that need to be pressed together with the hotkey.
+    ///
+    pub fn with_extra_keys(mut self, extra_keys: &[VKey]) -> Binding<T> {
+        self.extra_keys = extra_keys.to_vec();
+        self
+    }
+
+    /// Set the options that the hotkey is registered with.
+    ///
+    pub fn with_options(mut self, options: RegisterOptions) -> Binding<T> {
+        self.options = options;
+        self
+    }
+}
+
+impl<T> fmt::Debug for Binding<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Binding")
+            .field("key", &self.key)
+            .field("modifiers", &self.modifiers)
+            .field("extra_keys", &self.extra_keys)
+            .field("options", &self.options)
+            .finish_non_exhaustive()
+    }
+}
+
+/// The maximum time that `HotkeyManagerImpl::event_loop_with_shutdown` waits for hotkey events
+/// before checking its stop flag again.
+///
+pub const SHUTDOWN_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
 pub trait HotkeyManagerImpl<T> {
+    /// Create a new `HotkeyManager`. The `HotkeyId`s are taken from a process-global id pool
+    /// that is shared by all `HotkeyManager`s created with `new`, so their ids never collide and
+    /// the ids of unregistered hotkeys are reused by any of them.
+    ///
+    /// # Note
+    /// The shared ids only keep the bookkeeping collision-free. The hotkey events are still
+    /// delivered to the thread that registered the hotkey, so each `HotkeyManager` has to run its
+    /// own event loop on its own thread.
+    ///
     fn new() -> Self;
 
+    /// Same as `new`, but the `HotkeyId`s are counted by the `HotkeyManager` itself, starting
+    /// from `id_offset`, instead of being taken from the shared id pool. The id `i32::MIN` is
+    /// reserved for `can_register` and never handed out.
+    ///
+    fn new_with_id_offset(id_offset: i32) -> Self;
+
     /// Register a new hotkey with additional required extra keys.
     ///
     /// This will try to register the specified hotkey with windows, but not actively listen for it.
@@ -57,19 +518,31 @@ pub trait HotkeyManagerImpl<T> {
     /// # Arguments
     ///
     /// * `key` - The main hotkey. For example `VKey::Return` for the CTRL + ALT + ENTER
-    /// combination.
+    ///   combination.
     ///
     /// * `key_modifiers` - The modifier keys that need to be combined with the main key. The
-    /// modifier keys are the keys that need to be pressed in addition to the main hotkey in order
-    /// for the hotkey event to fire. For example `&[ModKey::Ctrl, ModKey::Alt]` for the
-    /// CTRL + ALT + ENTER combination.
+    ///   modifier keys are the keys that need to be pressed in addition to the main hotkey in order
+    ///   for the hotkey event to fire. For example `&[ModKey::Ctrl, ModKey::Alt]` or
+    ///   `ModKey::Ctrl | ModKey::Alt` for the CTRL + ALT + ENTER combination. Anything that
+    ///   converts into `Modifiers` is accepted, like a single `ModKey`, an array, a slice or a
+    ///   `&Vec<ModKey>`. The modifiers may be empty, which is common for keys
+    ///   without a regular use like the media keys (`VKey::MediaPlayPause`, `VKey::VolumeUp`) or
+    ///   the browser keys (`VKey::BrowserBack`).
     ///
     /// * `extra_keys` - A list of additional VKeys that also need to be pressed for the hotkey
-    /// callback to be executed. This is enforced after the windows hotkey event is fired, but
-    /// before executing the callback. So these keys need to be pressed before the main hotkey.
+    ///   callback to be executed. This is enforced after the windows hotkey event is fired, but
+    ///   before executing the callback. So these keys need to be pressed before the main hotkey.
+    ///   This can also be used to require the left or right modifier key, for example
+    ///   `&[VKey::RMenu]` together with `ModKey::Alt` for RIGHT ALT only.
     ///
     /// * `callback` - A callback function or closure that will be executed when the hotkey is
-    /// triggered. The return type for all callbacks in the same HotkeyManager must be the same.
+    ///   triggered. The return type for all callbacks in the same HotkeyManager must be the same.
+    ///
+    /// # Errors
+    /// If the key combination is already registered by this `HotkeyManager`,
+    /// `HkError::AlreadyRegistered` is returned with the id of the existing hotkey, without trying
+    /// to register it with windows. Combinations in use by other applications are reported as
+    /// `HkError::AlreadyRegistered` without an id.
     ///
     /// # Windows API Functions used
     /// - <https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-registerhotkey>
@@ -77,101 +550,1539 @@ pub trait HotkeyManagerImpl<T> {
     fn register_extrakeys(
         &mut self,
         key: VKey,
-        key_modifiers: &[ModKey],
+        key_modifiers: impl Into<Modifiers>,
         extra_keys: &[VKey],
         callback: impl Fn() -> T + Send + 'static,
     ) -> Result<HotkeyId, HkError>;
 
-    /// Same as `register_extrakeys` but without extra keys.
+    /// Same as `register_extrakeys`, but with additional `RegisterOptions` that control how the
+    /// hotkey is registered. For example `RegisterOptions::allow_repeat` can be set to keep
+    /// triggering the hotkey while the keys are held down.
+    ///
+    /// The callback can be a `FnMut` closure that mutates its captured state.
     ///
     /// # Windows API Functions used
     /// - <https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-registerhotkey>
     ///
-    fn register(
+    fn register_extrakeys_opts(
         &mut self,
         key: VKey,
         key_modifiers: &[ModKey],
-        callback: impl Fn() -> T + Send + 'static,
+        extra_keys: &[VKey],
+        options: RegisterOptions,
+        mut callback: impl FnMut() -> T + Send + 'static,
+    ) -> Result<HotkeyId, HkError> {
+        self.register_ctx_opts(key, key_modifiers, extra_keys, options, move |_| callback())
+    }
+
+    /// Same as `register`, but the callback gets a `HotkeyContext` with the id, the main key and
+    /// the modifier keys of the triggered hotkey. This allows sharing one handler between multiple
+    /// hotkeys.
+    ///
+    /// # Windows API Functions used
+    /// - <https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-registerhotkey>
+    ///
+    fn register_ctx(
+        &mut self,
+        key: VKey,
+        key_modifiers: &[ModKey],
+        callback: impl Fn(&HotkeyContext) -> T + Send + 'static,
+    ) -> Result<HotkeyId, HkError> {
+        self.register_ctx_opts(
+            key,
+            key_modifiers,
+            &[],
+            RegisterOptions::default(),
+            callback,
+        )
+    }
+
+    /// Same as `register_extrakeys_opts`, but the callback gets a `HotkeyContext` like with
+    /// `register_ctx`. This is the most general way to register a hotkey, all other register
+    /// functions use it.
+    ///
+    /// # Windows API Functions used
+    /// - <https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-registerhotkey>
+    ///
+    fn register_ctx_opts(
+        &mut self,
+        key: VKey,
+        key_modifiers: &[ModKey],
+        extra_keys: &[VKey],
+        options: RegisterOptions,
+        callback: impl FnMut(&HotkeyContext) -> T + Send + 'static,
     ) -> Result<HotkeyId, HkError>;
 
-    /// Unregister a hotkey. This will prevent the hotkey from being triggered in the future.
+    /// Same as `register_extrakeys`, but the callback is executed if any one of the `any_of` extra
+    /// keys is pressed, instead of requiring all of them. For example `CTRL + Q` can be restricted
+    /// to only trigger while either the left or the right shift key is pressed.
     ///
     /// # Windows API Functions used
-    /// - <https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-unregisterhotkey>
+    /// - <https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-registerhotkey>
     ///
-    fn unregister(&mut self, id: HotkeyId) -> Result<(), HkError>;
+    fn register_extrakeys_any(
+        &mut self,
+        key: VKey,
+        key_modifiers: &[ModKey],
+        any_of: &[VKey],
+        callback: impl Fn() -> T + Send + 'static,
+    ) -> Result<HotkeyId, HkError> {
+        let options = RegisterOptions {
+            extra_key_match: ExtraKeyMatch::Any,
+            ..Default::default()
+        };
+        self.register_extrakeys_opts(key, key_modifiers, any_of, options, callback)
+    }
 
-    /// Unregister all registered hotkeys. This will be called automatically when dropping the
-    /// HotkeyManager instance.
+    /// Same as `register_extrakeys`, but the callback is only executed if none of the `forbidden`
+    /// keys are pressed in addition to the `required` extra keys. This can be used to distinguish
+    /// between `CTRL + A` and `CTRL + SHIFT + A` when using extra keys.
     ///
     /// # Windows API Functions used
-    /// - <https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-unregisterhotkey>
+    /// - <https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-registerhotkey>
     ///
-    fn unregister_all(&mut self) -> Result<(), HkError>;
+    fn register_with_exclusions(
+        &mut self,
+        key: VKey,
+        key_modifiers: &[ModKey],
+        required: &[VKey],
+        forbidden: &[VKey],
+        callback: impl Fn() -> T + Send + 'static,
+    ) -> Result<HotkeyId, HkError> {
+        let options = RegisterOptions {
+            forbidden_keys: forbidden.to_vec(),
+            ..Default::default()
+        };
+        self.register_extrakeys_opts(key, key_modifiers, required, options, callback)
+    }
 
-    /// Wait for a single a hotkey event and execute the callback if all keys match. This returns
-    /// the callback result if it was not interrupted. The function call will block until a hotkey
-    /// is triggered or it is interrupted.
+    /// Same as `register_extrakeys`, but the callback can be a `FnMut` closure that mutates its
+    /// captured state, for example a counter or a toggle.
     ///
-    /// If the event is interrupted, `None` is returned, otherwise `Some` is returned with the
-    /// return value of the executed callback function.
+    /// # Windows API Functions used
+    /// - <https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-registerhotkey>
     ///
-    /// ## Windows API Functions used
-    /// - <https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-getmessagew>
+    fn register_extrakeys_mut(
+        &mut self,
+        key: VKey,
+        key_modifiers: &[ModKey],
+        extra_keys: &[VKey],
+        callback: impl FnMut() -> T + Send + 'static,
+    ) -> Result<HotkeyId, HkError> {
+        self.register_extrakeys_opts(
+            key,
+            key_modifiers,
+            extra_keys,
+            RegisterOptions::default(),
+            callback,
+        )
+    }
+
+    /// Same as `register_extrakeys_mut` but without extra keys.
+    ///
+    /// # Windows API Functions used
+    /// - <https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-registerhotkey>
     ///
-    fn handle_hotkey(&self) -> Option<T>;
+    fn register_mut(
+        &mut self,
+        key: VKey,
+        key_modifiers: &[ModKey],
+        callback: impl FnMut() -> T + Send + 'static,
+    ) -> Result<HotkeyId, HkError> {
+        self.register_extrakeys_mut(key, key_modifiers, &[], callback)
+    }
 
-    /// Run the event loop, listening for hotkeys. This will run indefinitely until interrupted and
-    /// execute any hotkeys registered before.
+    /// Same as `register_mut`, but the callback is executed on a worker thread instead of the
+    /// thread that handles the hotkey events. This keeps the event loop responsive while the
+    /// callback does heavy work. The return values of the callback are sent to the returned
+    /// `Receiver`.
+    ///
+    /// The hotkey event itself is handled immediately with `T::default()` as return value. The
+    /// callbacks of all hotkeys registered this way share a single worker thread that executes
+    /// them once per press, in the order of the presses. A slow callback therefore delays the
+    /// callbacks of the other hotkeys. The `Receiver` is disconnected once the hotkey is
+    /// unregistered or its callback is replaced and the remaining presses were executed. If the
+    /// callback panics, further presses of the hotkey are ignored.
+    ///
+    /// # Windows API Functions used
+    /// - <https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-registerhotkey>
     ///
-    fn event_loop(&self);
+    fn register_async_dispatch<R: Send + 'static>(
+        &mut self,
+        key: VKey,
+        key_modifiers: &[ModKey],
+        callback: impl FnMut() -> R + Send + 'static,
+    ) -> Result<(HotkeyId, Receiver<R>), HkError>
+    where
+        T: Default,
+    {
+        let (result_snd, result_rec) = mpsc::channel();
+        let callback = Arc::new(Mutex::new(callback));
 
-    /// Get an `InterruptHandle` for this `HotkeyManager` that can be used to interrupt the event
-    /// loop.
+        // The jobs share the callback and sender, the receiver disconnects when the last is dropped
+        let id = self.register_mut(key, key_modifiers, move || {
+            let callback = Arc::clone(&callback);
+            let result_snd = result_snd.clone();
+            let _ = async_worker().send(Box::new(move || {
+                // A poisoned lock means that the callback panicked before
+                if let Ok(mut callback) = callback.lock() {
+                    // The results are discarded if the receiver was dropped, the callback still runs
+                    let _ = result_snd.send(callback());
+                }
+            }));
+            T::default()
+        })?;
+
+        Ok((id, result_rec))
+    }
+
+    /// Same as `register_extrakeys` but without extra keys.
     ///
-    fn interrupt_handle(&self) -> InterruptHandle;
-}
+    /// The modifier keys can be given as a slice of `ModKey`s or as `Modifiers`, for example
+    /// `ModKey::Ctrl | ModKey::Shift`.
+    ///
+    /// # Windows API Functions used
+    /// - <https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-registerhotkey>
+    ///
+    fn register(
+        &mut self,
+        key: VKey,
+        key_modifiers: impl Into<Modifiers>,
+        callback: impl Fn() -> T + Send + 'static,
+    ) -> Result<HotkeyId, HkError>;
 
-/// The `InterruptHandle` can be used to interrupt the event loop of the originating `HotkeyManager`.
-/// This handle can be used from any thread and can be used multiple times.
-///
-/// # Note
-/// This handle will technically stay valid even after the `HotkeyManager` is dropped, but it will
-/// simply not do anything.
-///
-#[cfg(windows)]
-pub struct InterruptHandle(HWND);
+    /// Register a hotkey that toggles a shared state, for example `CTRL + ALT + M` to toggle mute.
+    /// Every press flips the returned `AtomicBool`, which starts as false, and the callback
+    /// returns the new state.
+    ///
+    /// The state can be read at any time from any thread. It stays valid after the hotkey is
+    /// unregistered, but isn't changed anymore.
+    ///
+    /// # Windows API Functions used
+    /// - <https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-registerhotkey>
+    ///
+    fn register_toggle(
+        &mut self,
+        key: VKey,
+        key_modifiers: &[ModKey],
+    ) -> Result<(HotkeyId, Arc<AtomicBool>), HkError>
+    where
+        T: From<bool>,
+    {
+        let state = Arc::new(AtomicBool::new(false));
 
-#[cfg(windows)]
-unsafe impl Sync for InterruptHandle {}
+        let toggled = state.clone();
+        let id = self.register(key, key_modifiers, move || {
+            // `fetch_xor` returns the previous state
+            T::from(!toggled.fetch_xor(true, Ordering::SeqCst))
+        })?;
 
-#[cfg(windows)]
-unsafe impl Send for InterruptHandle {}
+        Ok((id, state))
+    }
 
-#[cfg(windows)]
-impl InterruptHandle {
-    /// Interrupt the evet loop of the associated `HotkeyManager`.
+    /// Same as `register`, but the callback is only executed when the hotkey is pressed twice
+    /// within `max_interval`. Single presses are ignored. After a double press, the next press
+    /// starts counting from the beginning again, so pressing the hotkey three times in a row only
+    /// executes the callback once.
     ///
-    pub fn interrupt(&self) {
-        unsafe {
-            PostMessageW(self.0, WM_NULL, 0, 0);
-        }
+    /// # Windows API Functions used
+    /// - <https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-registerhotkey>
+    ///
+    fn register_double(
+        &mut self,
+        key: VKey,
+        key_modifiers: &[ModKey],
+        max_interval: Duration,
+        callback: impl Fn() -> T + Send + 'static,
+    ) -> Result<HotkeyId, HkError> {
+        let options = RegisterOptions {
+            double_press: Some(max_interval),
+            ..Default::default()
+        };
+        self.register_extrakeys_opts(key, key_modifiers, &[], options, callback)
     }
-}
 
-/// Get the global keystate for a given Virtual Key.
-///
-/// Return true if the key is pressed, false otherwise.
-///
-/// ## Windows API Functions used
-/// - <https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-getasynckeystate>
-///
-#[cfg(windows)]
-pub fn get_global_keystate(vk: VKey) -> bool {
-    // Most significant bit represents key state (1 => pressed, 0 => not pressed)
-    let key_state = unsafe { GetAsyncKeyState(vk.to_vk_code()) };
-    // Get most significant bit only
-    let key_state = key_state as u32 >> 31;
+    /// Same as `register`, but the main key is a mouse button. See the `mouse` module for how
+    /// mouse hotkeys are handled.
+    ///
+    /// # Windows API Functions used
+    /// - <https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-setwindowshookexw>
+    ///
+    #[cfg(feature = "mouse")]
+    fn register_mouse(
+        &mut self,
+        button: mouse::MouseButton,
+        key_modifiers: &[ModKey],
+        callback: impl Fn() -> T + Send + 'static,
+    ) -> Result<HotkeyId, HkError> {
+        self.register(button.to_vkey(), key_modifiers, callback)
+    }
 
-    key_state == 1
+    /// Same as `register`, but the key events of the hotkey are swallowed, so they don't reach the
+    /// foreground application. `RegisterHotKey` can't do this, so suppressing hotkeys are
+    /// handled by a low-level keyboard hook (`WH_KEYBOARD_LL`) that is installed on the thread of
+    /// the `HotkeyManager` for the first suppressing hotkey.
+    ///
+    /// The key press is suppressed as soon as the combination is pressed, so the decision can't
+    /// depend on the callback or on the extra keys and conditions, which are only checked when
+    /// the hotkey event is handled. The release of the main key is suppressed as well.
+    ///
+    /// ## Constraints of low-level hooks
+    /// The hook is called for every key event of the system, from inside the message handling of
+    /// the `HotkeyManager` thread, and the keyboard input of the whole system waits until it
+    /// returns. The hook itself only queues the hotkey events, but the events can only be
+    /// processed while the thread is waiting for messages. While a callback runs, every key
+    /// press is delayed, so callbacks must be fast and the event loop must keep running. If the
+    /// hook doesn't return within the `LowLevelHooksTimeout` of the system, windows passes the
+    /// key on and might silently remove the hook.
+    ///
+    /// # Windows API Functions used
+    /// - <https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-setwindowshookexw>
+    ///
+    #[cfg(feature = "hook")]
+    fn register_suppressing(
+        &mut self,
+        key: VKey,
+        key_modifiers: &[ModKey],
+        callback: impl Fn() -> T + Send + 'static,
+    ) -> Result<HotkeyId, HkError> {
+        let options = RegisterOptions {
+            suppress: true,
+            ..Default::default()
+        };
+        self.register_extrakeys_opts(key, key_modifiers, &[], options, callback)
+    }
+
+    /// Same as `register`, but the hotkey gets a human-readable `label`. The label can be used to
+    /// display the hotkey and to find it again using `find_by_label`.
+    ///
+    /// # Windows API Functions used
+    /// - <https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-registerhotkey>
+    ///
+    fn register_labeled(
+        &mut self,
+        label: impl Into<String>,
+        key: VKey,
+        key_modifiers: &[ModKey],
+        callback: impl Fn() -> T + Send + 'static,
+    ) -> Result<HotkeyId, HkError> {
+        let options = RegisterOptions {
+            label: Some(label.into()),
+            ..Default::default()
+        };
+        self.register_extrakeys_opts(key, key_modifiers, &[], options, callback)
+    }
+
+    /// Same as `register_extrakeys`, but the key, modifier keys and extra keys are taken from a
+    /// `KeyCombination`.
+    ///
+    /// # Windows API Functions used
+    /// - <https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-registerhotkey>
+    ///
+    fn register_combination(
+        &mut self,
+        combination: &KeyCombination,
+        callback: impl Fn() -> T + Send + 'static,
+    ) -> Result<HotkeyId, HkError> {
+        self.register_extrakeys(
+            combination.key,
+            &combination.modifiers,
+            &combination.extra_keys,
+            callback,
+        )
+    }
+
+    /// Same as `register`, but the callback is only executed if the `condition` returns true.
+    /// This can be used for context sensitive hotkeys, for example hotkeys that are only active
+    /// while a specific application is focused.
+    ///
+    /// The condition is evaluated every time the hotkey is triggered, before executing the
+    /// callback. If the condition returns false, the hotkey event is ignored.
+    ///
+    /// # Windows API Functions used
+    /// - <https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-registerhotkey>
+    ///
+    fn register_conditional(
+        &mut self,
+        key: VKey,
+        key_modifiers: &[ModKey],
+        condition: impl Fn() -> bool + Send + Sync + 'static,
+        callback: impl Fn() -> T + Send + 'static,
+    ) -> Result<HotkeyId, HkError> {
+        let options = RegisterOptions {
+            condition: Some(Arc::new(condition)),
+            ..Default::default()
+        };
+        self.register_extrakeys_opts(key, key_modifiers, &[], options, callback)
+    }
+
+    /// Same as `register`, but the callback is only executed while the foreground window belongs
+    /// to a process with the executable name `exe_name`, for example `"notepad.exe"`. The names
+    /// are compared case-insensitively.
+    ///
+    /// # Note
+    /// The foreground process is queried using the windows API every time the hotkey is
+    /// triggered.
+    ///
+    /// # Windows API Functions used
+    /// - <https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-registerhotkey>
+    /// - See `foreground::foreground_exe_name`
+    ///
+    fn register_for_app(
+        &mut self,
+        key: VKey,
+        key_modifiers: &[ModKey],
+        exe_name: &str,
+        callback: impl Fn() -> T + Send + 'static,
+    ) -> Result<HotkeyId, HkError> {
+        let condition = foreground::foreground_app_condition(exe_name);
+        self.register_conditional(key, key_modifiers, condition, callback)
+    }
+
+    /// Register multiple hotkeys at once. On success, the `HotkeyId`s of the hotkeys are returned
+    /// in the same order as the bindings.
+    ///
+    /// The registration is all or nothing: If any of the hotkeys fails to register, all hotkeys
+    /// from the batch that were already registered are unregistered again and the error of the
+    /// failed registration is returned.
+    ///
+    /// # Windows API Functions used
+    /// - <https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-registerhotkey>
+    /// - <https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-unregisterhotkey>
+    ///
+    fn register_many<I>(&mut self, bindings: I) -> Result<Vec<HotkeyId>, HkError>
+    where
+        I: IntoIterator<Item = Binding<T>>,
+        T: 'static,
+    {
+        let mut ids = Vec::new();
+        for binding in bindings {
+            let res = self.register_extrakeys_opts(
+                binding.key,
+                &binding.modifiers,
+                &binding.extra_keys,
+                binding.options,
+                binding.callback,
+            );
+
+            match res {
+                Ok(id) => ids.push(id),
+                Err(err) => {
+                    // Roll back the batch in reverse order. The hotkeys were just registered by
+                    // this manager, so there is nothing more to do if unregistering fails
+                    for id in ids.into_iter().rev() {
+                        let _ = self.unregister(id);
+                    }
+                    return Err(err);
+                }
+            }
+        }
+
+        Ok(ids)
+    }
+
+    /// Register the bindings of a `HotkeyConfig`. The `dispatch` function maps the action name of
+    /// every binding to its callback, or returns `None` for unknown actions, so they are reported
+    /// as `HkError::UnknownAction`.
+    ///
+    /// Unlike `register_many`, every binding is registered on its own, so one invalid binding
+    /// doesn't prevent the others from being registered. The results are returned in the order
+    /// of the bindings. A binding fails with the parse error of its keys, with
+    /// `HkError::UnknownAction` if `dispatch` doesn't know the action, or with the error of the
+    /// registration.
+    ///
+    /// The action name becomes the label of the hotkey, so `dump_config` describes the hotkeys
+    /// with their action names. Bindings with `enabled` set to false are disabled right after
+    /// registering them. If disabling fails, the hotkey is unregistered again and the error is
+    /// returned.
+    ///
+    /// # Windows API Functions used
+    /// - <https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-registerhotkey>
+    /// - <https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-unregisterhotkey>
+    ///
+    fn apply_config(
+        &mut self,
+        config: &config::HotkeyConfig,
+        mut dispatch: impl FnMut(&str) -> Option<Box<dyn Fn() -> T + Send>>,
+    ) -> Vec<Result<HotkeyId, HkError>>
+    where
+        T: 'static,
+    {
+        config
+            .bindings
+            .iter()
+            .map(|binding| {
+                let combination = binding.combination()?;
+                let callback = dispatch(&binding.action)
+                    .ok_or_else(|| HkError::UnknownAction(binding.action.clone()))?;
+                let options = RegisterOptions {
+                    label: Some(binding.action.clone()),
+                    ..Default::default()
+                };
+                let id = self.register_extrakeys_opts(
+                    combination.key,
+                    &combination.modifiers,
+                    &combination.extra_keys,
+                    options,
+                    callback,
+                )?;
+
+                if !binding.enabled {
+                    if let Err(err) = self.disable(id) {
+                        let _ = self.unregister(id);
+                        return Err(err);
+                    }
+                }
+
+                Ok(id)
+            })
+            .collect()
+    }
+
+    /// Register `count` hotkeys for consecutive virtual keycodes, starting at `start`, with the
+    /// same modifiers. The callback is shared by all of them and gets the zero-based offset of
+    /// the triggered key from `start`. For example `VKey::Vk1` with a count of 9 registers
+    /// `1` to `9`, so one callback can switch to the workspace `offset + 1`.
+    ///
+    /// The `HotkeyId`s are returned in the order of the keys. If any keycode of the range is
+    /// outside of the valid virtual keycodes (`0x01` to `0xFE`), `HkError::InvalidKey` is returned
+    /// before registering anything. Like `register_many`, the registration is all or nothing.
+    ///
+    /// # Windows API Functions used
+    /// - <https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-registerhotkey>
+    /// - <https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-unregisterhotkey>
+    ///
+    fn register_range(
+        &mut self,
+        start: VKey,
+        count: usize,
+        key_modifiers: &[ModKey],
+        callback: impl Fn(usize) -> T + Send + Sync + 'static,
+    ) -> Result<Vec<HotkeyId>, HkError>
+    where
+        T: 'static,
+    {
+        let start = start.to_vk_code() as i64;
+        // Collecting stops at the first invalid keycode, so the offset never gets large
+        let keys = (0..count)
+            .map(|offset| match start + offset as i64 {
+                // Use the named variants, so the keys match the patterns of their `VKey`s
+                vk_code @ 0x01..=0xFE => Ok(VKey::CustomKeyCode(vk_code as i32).to_named()),
+                vk_code => Err(HkError::InvalidKey(format!("0x{:02X}", vk_code))),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let callback = Arc::new(callback);
+        let bindings = keys.into_iter().enumerate().map(|(offset, key)| {
+            let callback = callback.clone();
+            Binding::new(key, key_modifiers, move || callback(offset))
+        });
+        self.register_many(bindings)
+    }
+
+    /// Replace the callback of a registered hotkey. The `HotkeyId`, the extra keys and the
+    /// registration with windows stay the same, so the new callback is executed for all future
+    /// triggers of the hotkey.
+    ///
+    /// If there is no hotkey with the given id, `HkError::UnknownId` is returned.
+    ///
+    fn replace_callback(
+        &mut self,
+        id: HotkeyId,
+        callback: impl FnMut() -> T + Send + 'static,
+    ) -> Result<(), HkError>;
+
+    /// Register a hotkey like `register`, but if the key combination is already registered by this
+    /// `HotkeyManager`, the existing hotkey is replaced. This is useful when reloading a config,
+    /// where most bindings usually didn't change.
+    ///
+    /// Unlike `replace_callback`, this finds the hotkey by its key combination and replaces the
+    /// whole hotkey. Like with `register`, the new hotkey has no extra keys and the default
+    /// `RegisterOptions`. The returned id is the id of the new hotkey. The id of the replaced
+    /// hotkey must not be used anymore, even though it may be handed out again later.
+    ///
+    /// The replaced hotkey is kept until the new hotkey is registered. If registering the new
+    /// hotkey fails, the replaced hotkey is registered again and the error is returned. If that
+    /// fails as well, the replaced hotkey is disabled and `HkError::RollbackFailed` is returned.
+    ///
+    /// # Windows API Functions used
+    /// - <https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-unregisterhotkey>
+    /// - <https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-registerhotkey>
+    ///
+    fn register_replacing(
+        &mut self,
+        key: VKey,
+        key_modifiers: &[ModKey],
+        callback: impl Fn() -> T + Send + 'static,
+    ) -> Result<HotkeyId, HkError>;
+
+    /// Register a hotkey with a fallible callback, for example one that spawns a process or writes
+    /// a file. The `HotkeyManager` must have a `Result` as its callback return type, like
+    /// `HotkeyManager<Result<(), std::io::Error>>`. The errors of the callbacks are passed to the
+    /// error handler of `event_loop_try`, so the callbacks don't need to handle them themselves.
+    ///
+    /// This is the same as `register`, apart from requiring a `Result` return type.
+    ///
+    /// # Windows API Functions used
+    /// - <https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-registerhotkey>
+    ///
+    fn register_try(
+        &mut self,
+        key: VKey,
+        key_modifiers: &[ModKey],
+        callback: impl Fn() -> T + Send + 'static,
+    ) -> Result<HotkeyId, HkError>
+    where
+        T: CallbackResult,
+    {
+        self.register_extrakeys(key, key_modifiers, &[], callback)
+    }
+
+    /// Set a fallback callback that is executed for hotkey events with an id that doesn't belong
+    /// to any hotkey of this `HotkeyManager`. Without a fallback, these events are ignored. A
+    /// previously set fallback is replaced.
+    ///
+    /// The fallback only sees the events that are delivered to this `HotkeyManager`, for example
+    /// events that were still queued when their hotkey was unregistered, or hotkeys that other
+    /// code registered directly with windows for the same window or thread queue. Events of other
+    /// `HotkeyManager`s on the same thread don't reach it. This helps to track down "phantom"
+    /// hotkey events.
+    ///
+    fn set_fallback(&mut self, callback: impl FnMut(HotkeyId) -> T + Send + 'static);
+
+    /// Change the key combination of a registered hotkey while keeping the same `HotkeyId`. The
+    /// callback, extra keys and options of the hotkey are preserved.
+    ///
+    /// The old combination is unregistered before the new one is registered. If registering the
+    /// new combination fails, the old combination is registered again and the error is returned,
+    /// so the hotkey stays usable with its previous combination. If the old combination can't be
+    /// registered again either, the hotkey is disabled and `HkError::RollbackFailed` is returned.
+    /// For a disabled hotkey only the stored combination is changed, it is registered when the
+    /// hotkey is enabled again.
+    ///
+    /// If there is no hotkey with the given id, `HkError::UnknownId` is returned. If another
+    /// hotkey of this `HotkeyManager` already uses the new combination,
+    /// `HkError::AlreadyRegistered` with the id of that hotkey is returned.
+    ///
+    /// # Windows API Functions used
+    /// - <https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-unregisterhotkey>
+    /// - <https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-registerhotkey>
+    ///
+    fn rebind(
+        &mut self,
+        id: HotkeyId,
+        key: VKey,
+        key_modifiers: impl Into<Modifiers>,
+    ) -> Result<(), HkError>;
+
+    /// Unregister a hotkey. This will prevent the hotkey from being triggered in the future.
+    ///
+    /// The id of the unregistered hotkey will be reused for hotkeys that are registered later, so
+    /// it must not be used anymore after unregistering.
+    ///
+    /// # Windows API Functions used
+    /// - <https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-unregisterhotkey>
+    ///
+    fn unregister(&mut self, id: HotkeyId) -> Result<(), HkError>;
+
+    /// Unregister multiple hotkeys at once.
+    ///
+    /// Unlike calling `unregister` in a loop, this doesn't stop at the first failure. All hotkeys
+    /// are attempted and only the hotkeys that were successfully unregistered are removed from
+    /// the `HotkeyManager`. If any of them failed, `HkError::Multiple` is returned with the id and
+    /// error of each hotkey that is still registered.
+    ///
+    /// # Windows API Functions used
+    /// - <https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-unregisterhotkey>
+    ///
+    fn unregister_many(&mut self, ids: &[HotkeyId]) -> Result<(), HkError> {
+        let errors = ids
+            .iter()
+            .filter_map(|id| self.unregister(*id).err().map(|err| (*id, err)))
+            .collect();
+
+        HkError::from_errors(errors)
+    }
+
+    /// Temporarily disable a registered hotkey. The hotkey is unregistered with windows, but keeps
+    /// its `HotkeyId`, callback and options, so it can be enabled again using `enable`. Disabling
+    /// a hotkey that is already disabled does nothing.
+    ///
+    /// If there is no hotkey with the given id, `HkError::UnknownId` is returned.
+    ///
+    /// # Windows API Functions used
+    /// - <https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-unregisterhotkey>
+    ///
+    fn disable(&mut self, id: HotkeyId) -> Result<(), HkError>;
+
+    /// Enable a hotkey that was disabled using `disable` by registering its key combination with
+    /// windows again. Enabling a hotkey that is already enabled does nothing.
+    ///
+    /// If there is no hotkey with the given id, `HkError::UnknownId` is returned. If registering
+    /// the key combination fails, the hotkey stays disabled and the error is returned.
+    ///
+    /// # Windows API Functions used
+    /// - <https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-registerhotkey>
+    ///
+    fn enable(&mut self, id: HotkeyId) -> Result<(), HkError>;
+
+    /// Trigger the hotkey with the given id as if its key combination was pressed, for example to
+    /// test the callbacks or to run the action of a hotkey from the application itself. A
+    /// `WM_HOTKEY` message with the registered key combination is posted to the message queue, so
+    /// the callback is executed by the event loop just like for a real key press. The extra keys
+    /// and other conditions of the hotkey are still checked when the event is handled.
+    ///
+    /// If there is no hotkey with the given id, `HkError::UnknownId` is returned. If the hotkey
+    /// is disabled or the `HotkeyManager` is paused, `HkError::Inactive` is returned.
+    ///
+    /// # Windows API Functions used
+    /// - <https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-postmessagew>
+    /// - <https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-postthreadmessagew>
+    ///
+    fn trigger(&self, id: HotkeyId) -> Result<(), HkError>;
+
+    /// Temporarily disable all hotkeys, for example while the application is in a mode where
+    /// global hotkeys would be disruptive. All hotkeys are unregistered with windows, but keep
+    /// their `HotkeyId`s, callbacks and options. Hotkeys registered while paused are only
+    /// registered with windows when resuming.
+    ///
+    /// If unregistering fails for some of the hotkeys, `HkError::Multiple` is returned with the
+    /// ids that failed. These hotkeys are not triggered while paused either.
+    ///
+    /// # Windows API Functions used
+    /// - <https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-unregisterhotkey>
+    ///
+    fn pause_all(&mut self) -> Result<(), HkError>;
+
+    /// Register all hotkeys with windows again after `pause_all`. Hotkeys that were disabled
+    /// using `disable` stay disabled.
+    ///
+    /// Registering is attempted for all hotkeys. If it fails for some of them, `HkError::Multiple`
+    /// is returned with the ids that failed. These hotkeys are disabled and can be retried using
+    /// `enable`.
+    ///
+    /// # Windows API Functions used
+    /// - <https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-registerhotkey>
+    ///
+    fn resume_all(&mut self) -> Result<(), HkError>;
+
+    /// Unregister the hotkey that was registered for the given key combination. The order of the
+    /// modifier keys doesn't matter.
+    ///
+    /// If no hotkey is registered for the combination, `HkError::NotRegistered` is returned.
+    ///
+    /// # Windows API Functions used
+    /// - <https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-unregisterhotkey>
+    ///
+    fn unregister_combo(&mut self, key: VKey, key_modifiers: &[ModKey]) -> Result<(), HkError>;
+
+    /// Unregister all registered hotkeys. This will be called automatically when dropping the
+    /// HotkeyManager instance.
+    ///
+    /// All hotkeys are attempted, even if some of them fail to unregister. The failed hotkeys
+    /// stay registered in the `HotkeyManager` and are returned in `HkError::Multiple`.
+    ///
+    /// # Windows API Functions used
+    /// - <https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-unregisterhotkey>
+    ///
+    fn unregister_all(&mut self) -> Result<(), HkError>;
+
+    /// Unregister all registered hotkeys and reset the `HotkeyManager` to the state it was
+    /// created in.
+    ///
+    /// Unlike `unregister_all`, this also resets the id counter back to the initial id offset of
+    /// the `HotkeyManager`, so new ids are handed out starting from the offset again. If any
+    /// hotkey fails to unregister, the id counter is not reset. `HotkeyManager`s that take their
+    /// ids from the shared id pool (see `new`) don't reset it, since other `HotkeyManager`s
+    /// might still use its ids.
+    ///
+    /// # Windows API Functions used
+    /// - <https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-unregisterhotkey>
+    ///
+    fn clear(&mut self) -> Result<(), HkError>;
+
+    /// Get the ids of all hotkeys that are currently registered in this `HotkeyManager`, ordered
+    /// by id.
+    ///
+    fn registered_ids(&self) -> Vec<HotkeyId>;
+
+    /// Get the id, key combination and extra keys of all hotkeys that are currently registered in
+    /// this `HotkeyManager`, ordered by id.
+    ///
+    /// # Note
+    /// The hotkeys are returned as owned copies, since the threadsafe `HotkeyManager` stores the
+    /// hotkeys on its backend thread.
+    ///
+    fn registered(&self) -> Vec<HotkeyInfo>;
+
+    /// Describe all hotkeys that are currently registered in this `HotkeyManager`, ordered by id.
+    /// The descriptors contain the key combination, extra keys, label and enabled state of every
+    /// hotkey, but not the callback. With the `serde` feature they can be saved and loaded again
+    /// through a `config::HotkeyConfig` and `apply_config`.
+    ///
+    fn dump_config(&self) -> Vec<config::BindingDescriptor>;
+
+    /// Get the accelerator table entries for all hotkeys that are currently registered in this
+    /// `HotkeyManager`, ordered by id. The command id of every entry is the id of its hotkey.
+    ///
+    /// The extra keys and options of the hotkeys are dropped and hotkeys that can't be
+    /// represented as an accelerator are skipped, see the `accel` module.
+    ///
+    fn to_accel_table(&self) -> Vec<accel::Accel> {
+        self.registered()
+            .iter()
+            .filter_map(|info| accel::Accel::from_hotkey(info.id, info.key, &info.modifiers))
+            .collect()
+    }
+
+    /// Check if a hotkey is registered for the given key combination in this `HotkeyManager`. The
+    /// order of the modifier keys doesn't matter.
+    ///
+    /// # Note
+    /// Only hotkeys registered by this `HotkeyManager` are considered. Hotkeys registered by other
+    /// applications or other `HotkeyManager`s are not detected.
+    ///
+    fn is_registered(&self, key: VKey, key_modifiers: &[ModKey]) -> bool;
+
+    /// Check if the given key combination could be registered right now, without keeping it
+    /// registered. Unlike `is_registered`, this also detects hotkeys of other applications and
+    /// other `HotkeyManager`s, for example to validate a combination while the user types it.
+    ///
+    /// The combination is registered with windows using a reserved scratch id (`i32::MIN`) that
+    /// is never handed out for a hotkey, and unregistered again right away. Returns the error of
+    /// the registration if it fails, usually `HkError::AlreadyRegistered`. Combinations that are
+    /// registered by this `HotkeyManager` are reported as `HkError::AlreadyRegistered`, even while
+    /// it is paused.
+    ///
+    /// # Windows API Functions used
+    /// - <https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-registerhotkey>
+    /// - <https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-unregisterhotkey>
+    ///
+    fn can_register(&self, key: VKey, key_modifiers: &[ModKey]) -> Result<(), HkError>;
+
+    /// Get the label of the hotkey with the given id. Returns `None` if there is no hotkey with
+    /// the given id or if the hotkey has no label.
+    ///
+    /// # Note
+    /// The label is returned as an owned copy, since the threadsafe `HotkeyManager` stores the
+    /// hotkeys on its backend thread.
+    ///
+    fn label(&self, id: HotkeyId) -> Option<String> {
+        self.registered()
+            .into_iter()
+            .find(|info| info.id == id)
+            .and_then(|info| info.label)
+    }
+
+    /// Check if the hotkey with the given id is enabled, see `disable` and `enable`. Returns
+    /// `None` if there is no hotkey with the given id.
+    ///
+    /// Pausing the `HotkeyManager` with `pause_all` doesn't change whether the hotkeys are
+    /// enabled.
+    ///
+    fn is_enabled(&self, id: HotkeyId) -> Option<bool> {
+        self.registered()
+            .into_iter()
+            .find(|info| info.id == id)
+            .map(|info| info.enabled)
+    }
+
+    /// Find a hotkey by its label. If multiple hotkeys have the same label, the one with the
+    /// lowest id is returned.
+    ///
+    fn find_by_label(&self, label: &str) -> Option<HotkeyId> {
+        self.registered()
+            .into_iter()
+            .find(|info| info.label.as_deref() == Some(label))
+            .map(|info| info.id)
+    }
+
+    /// Get the number of hotkeys that are currently registered in this `HotkeyManager`.
+    ///
+    fn len(&self) -> usize;
+
+    /// Get the number of times the callback of the hotkey with the given id was executed. Hotkey
+    /// events that were ignored, for example because the extra keys were not pressed, are not
+    /// counted. Returns `None` if there is no hotkey with the given id.
+    ///
+    fn fire_count(&self, id: HotkeyId) -> Option<u64>;
+
+    /// Get the number of times the callback of each registered hotkey was executed. See
+    /// `fire_count`.
+    ///
+    fn fire_counts(&self) -> HashMap<HotkeyId, u64>;
+
+    /// Check if there are no hotkeys registered in this `HotkeyManager`.
+    ///
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Wait for a single a hotkey event and execute the callback if all keys match. This returns
+    /// the callback result if it was not interrupted. The function call will block until a hotkey
+    /// is triggered or it is interrupted.
+    ///
+    /// If the event is interrupted, `Ok(None)` is returned, otherwise `Ok(Some)` is returned with
+    /// the return value of the executed callback function. If reading from the message queue
+    /// fails, `HkError::Os` is returned with the windows error code.
+    ///
+    /// If the callback panics, the panic is caught and `HkError::CallbackPanicked` is returned, so
+    /// the `HotkeyManager` keeps working. Callbacks should still avoid panicking, since the panic
+    /// message is printed and any state that the callback was modifying might be inconsistent.
+    ///
+    /// ## Windows API Functions used
+    /// - <https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-getmessagew>
+    ///
+    fn handle_hotkey(&self) -> Result<Option<T>, HkError>;
+
+    /// Same as `handle_hotkey`, but errors while reading from the message queue are treated the
+    /// same way as interrupts and result in `None`.
+    ///
+    /// ## Windows API Functions used
+    /// - <https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-getmessagew>
+    ///
+    fn handle_hotkey_opt(&self) -> Option<T> {
+        self.handle_hotkey().ok().flatten()
+    }
+
+    /// Same as `handle_hotkey`, but also return the `HotkeyId` of the hotkey that was triggered
+    /// together with the return value of the executed callback. This allows telling apart
+    /// hotkeys that share the same callback return type.
+    ///
+    /// ## Windows API Functions used
+    /// - <https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-getmessagew>
+    ///
+    fn handle_hotkey_with_id(&self) -> Result<Option<(HotkeyId, T)>, HkError> {
+        Ok(self
+            .handle_hotkey_detailed()?
+            .map(|event| (event.id, event.result)))
+    }
+
+    /// Same as `handle_hotkey_with_id`, but return a `HotkeyEvent` that also contains the time and
+    /// cursor position of the `WM_HOTKEY` message. This can be used for hotkeys that act on the
+    /// window under the cursor, or to measure the time between hotkey events.
+    ///
+    /// ## Windows API Functions used
+    /// - <https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-getmessagew>
+    ///
+    fn handle_hotkey_detailed(&self) -> Result<Option<HotkeyEvent<T>>, HkError>;
+
+    /// Same as `handle_hotkey`, but wait at most for the given `timeout`. If the timeout elapses
+    /// before a hotkey is triggered, `Ok(None)` is returned without executing any callback.
+    ///
+    /// The timeout is rounded up to full milliseconds. Timeouts that are too large to be
+    /// represented in milliseconds by the windows API are treated as infinite.
+    ///
+    /// ## Windows API Functions used
+    /// - <https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-msgwaitformultipleobjects>
+    /// - <https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-peekmessagew>
+    ///
+    fn handle_hotkey_timeout(&self, timeout: Duration) -> Result<Option<T>, HkError>;
+
+    /// Handle a hotkey event that is already in the message queue without blocking. If there is
+    /// a hotkey event and the extra keys of the hotkey are pressed, the callback is executed and
+    /// its return value is returned. Otherwise `None` is returned right away.
+    ///
+    /// This can be used to handle hotkeys from an existing loop, for example the frame loop of a
+    /// game or GUI, without an additional thread.
+    ///
+    /// # Note
+    /// Only a single hotkey event is taken from the queue per call, even if it didn't lead to a
+    /// callback being executed. Interrupts are not handled by this function. If the callback
+    /// panics, the panic is caught and `None` is returned.
+    ///
+    /// ## Windows API Functions used
+    /// - <https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-peekmessagew>
+    ///
+    fn try_handle_hotkey(&self) -> Option<T>;
+
+    /// Handle all hotkey events that are already in the message queue without blocking and
+    /// return the return values of the executed callbacks in the order of the events. Hotkey
+    /// events whose extra keys are not pressed are taken from the queue without a result.
+    ///
+    /// This can be used together with `try_handle_hotkey` to process all hotkeys that were
+    /// triggered since the last frame of a frame loop.
+    ///
+    /// ## Windows API Functions used
+    /// - <https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-peekmessagew>
+    ///
+    fn drain_hotkeys(&self) -> Vec<T>;
+
+    /// Run the event loop, listening for hotkeys. This will run indefinitely until interrupted and
+    /// execute any hotkeys registered before.
+    ///
+    /// If reading from the message queue fails, the event loop stops and the error is returned.
+    /// Panicking callbacks don't stop the event loop.
+    ///
+    fn event_loop(&self) -> Result<(), HkError>;
+
+    /// Same as `event_loop`, but for callbacks that return a `Result`, see `register_try`. Every
+    /// error of a callback is passed to `on_error` together with the id of the hotkey, for example
+    /// to log it. The event loop keeps running if `on_error` returns `ControlFlow::Continue` and
+    /// returns `Ok(())` if it returns `ControlFlow::Break`.
+    ///
+    /// The errors of the callbacks are kept separate from `HkError`. If reading from the message
+    /// queue fails, the event loop stops and returns the `HkError` without calling `on_error`.
+    /// Panicking callbacks don't stop the event loop.
+    ///
+    fn event_loop_try(
+        &self,
+        mut on_error: impl FnMut(HotkeyId, T::Error) -> ControlFlow<()>,
+    ) -> Result<(), HkError>
+    where
+        T: CallbackResult,
+    {
+        loop {
+            match self.handle_hotkey_with_id() {
+                Ok(Some((id, result))) => {
+                    if let Err(err) = result.into_result() {
+                        if on_error(id, err).is_break() {
+                            return Ok(());
+                        }
+                    }
+                }
+                // A panicking callback doesn't stop the event loop
+                Err(HkError::CallbackPanicked(_)) => (),
+                Ok(None) => return Ok(()),
+                Err(err) => return Err(err),
+            }
+        }
+    }
+
+    /// Same as `event_loop`, but stop the event loop when the `stop` flag is set. When stopping,
+    /// all hotkeys are unregistered before returning.
+    ///
+    /// Since waiting for hotkey events blocks, the flag is checked at least every
+    /// `SHUTDOWN_POLL_INTERVAL` using `handle_hotkey_timeout`. Interrupting the event loop using an
+    /// `InterruptHandle` checks the flag right away.
+    ///
+    /// # Note
+    /// A thread that is blocked in a plain `event_loop` can't observe a flag. It can only be
+    /// stopped from another thread using an `InterruptHandle` or by posting `WM_QUIT` using a
+    /// `LoopQuitter`.
+    ///
+    /// ## Windows API Functions used
+    /// - <https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-msgwaitformultipleobjects>
+    /// - <https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-peekmessagew>
+    /// - <https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-unregisterhotkey>
+    ///
+    fn event_loop_with_shutdown(&mut self, stop: Arc<AtomicBool>) -> Result<(), HkError> {
+        while !stop.load(Ordering::SeqCst) {
+            match self.handle_hotkey_timeout(SHUTDOWN_POLL_INTERVAL) {
+                // A panicking callback doesn't stop the event loop
+                Ok(_) | Err(HkError::CallbackPanicked(_)) => (),
+                Err(err) => return Err(err),
+            }
+        }
+
+        self.unregister_all()
+    }
+
+    /// Get the id of the thread that owns the hotkeys of this `HotkeyManager`. Windows associates
+    /// registered hotkeys with the thread that registered them and only delivers their events to
+    /// that thread, so registering, unregistering and handling hotkeys must happen there.
+    /// Otherwise these functions fail with `HkError::WrongThread`. `try_handle_hotkey` and
+    /// `drain_hotkeys` don't return errors, they don't handle any hotkeys there.
+    ///
+    /// For the threadsafe `HotkeyManager`, this is the id of its backend thread. Returns `None`
+    /// if the backend is not bound to a thread, for example the `StubBackend`.
+    ///
+    fn owner_thread_id(&self) -> Option<u32>;
+
+    /// Get an `InterruptHandle` for this `HotkeyManager` that can be used to interrupt the event
+    /// loop.
+    ///
+    fn interrupt_handle(&self) -> InterruptHandle;
+
+    /// Get a `LoopQuitter` for this `HotkeyManager` that can be used to stop the event loop from
+    /// any thread by posting `WM_QUIT` to its message queue.
+    ///
+    fn quit_handle(&self) -> LoopQuitter;
+
+    /// Same as `register`, but instead of the `HotkeyId` a `HotkeyGuard` is returned that
+    /// unregisters the hotkey when it is dropped.
+    ///
+    /// The guard mutably borrows the `HotkeyManager` for its whole lifetime, but gives access to
+    /// it through `Deref` and `DerefMut`. This means that the guard can't be dropped while the
+    /// manager is handling hotkeys. The unregistration on drop only calls `UnregisterHotKey` and
+    /// removes the callback from the manager.
+    ///
+    /// # Windows API Functions used
+    /// - <https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-registerhotkey>
+    /// - <https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-unregisterhotkey>
+    ///
+    fn register_scoped(
+        &mut self,
+        key: VKey,
+        key_modifiers: &[ModKey],
+        callback: impl Fn() -> T + Send + 'static,
+    ) -> Result<HotkeyGuard<'_, T, Self>, HkError>
+    where
+        Self: Sized,
+    {
+        let id = self.register(key, key_modifiers, callback)?;
+        Ok(HotkeyGuard {
+            hkm: self,
+            id,
+            _phantom: PhantomData,
+        })
+    }
+
+    /// Get an iterator over the return values of the executed hotkey callbacks. Every call to
+    /// `next` blocks until a hotkey callback was executed, like `handle_hotkey`. Hotkey events
+    /// that don't lead to a callback being executed, for example because the extra keys were not
+    /// pressed, are skipped.
+    ///
+    /// The iterator ends when the `HotkeyManager` is interrupted through an `InterruptHandle`,
+    /// stopped through a `LoopQuitter` or when reading from the message queue fails. Panicking
+    /// callbacks are skipped and don't end the iteration. The error that ended the iteration can
+    /// be retrieved using `HotkeyEvents::error`.
+    ///
+    /// ## Windows API Functions used
+    /// - <https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-getmessagew>
+    ///
+    fn events(&self) -> HotkeyEvents<'_, T, Self>
+    where
+        Self: Sized,
+    {
+        HotkeyEvents {
+            hkm: self,
+            done: false,
+            error: None,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+/// Iterator over the return values of executed hotkey callbacks, returned by
+/// `HotkeyManagerImpl::events`.
+///
+pub struct HotkeyEvents<'a, T, M: HotkeyManagerImpl<T>> {
+    hkm: &'a M,
+    /// Whether the iteration ended, so `next` doesn't block again
+    done: bool,
+    /// The error that ended the iteration
+    error: Option<HkError>,
+    _phantom: PhantomData<T>,
+}
+
+impl<T, M: HotkeyManagerImpl<T>> HotkeyEvents<'_, T, M> {
+    /// Get the error that ended the iteration, if the iteration ended because reading from the
+    /// message queue failed.
+    ///
+    pub fn error(&self) -> Option<&HkError> {
+        self.error.as_ref()
+    }
+}
+
+impl<T, M: HotkeyManagerImpl<T>> Iterator for HotkeyEvents<'_, T, M> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        while !self.done {
+            match self.hkm.handle_hotkey() {
+                Ok(Some(ret)) => return Some(ret),
+                Err(HkError::CallbackPanicked(_)) => (),
+                Ok(None) => self.done = true,
+                Err(err) => {
+                    self.done = true;
+                    self.error = Some(err);
+                }
+            }
+        }
+
+        None
+    }
+}
+
+impl<T, M: HotkeyManagerImpl<T>> FusedIterator for HotkeyEvents<'_, T, M> {}
+
+/// A `HotkeyGuard` is returned by `HotkeyManagerImpl::register_scoped` and unregisters the
+/// associated hotkey when it is dropped. The `HotkeyManager` that the hotkey was registered with
+/// can be accessed through the guard.
+///
+pub struct HotkeyGuard<'a, T, M: HotkeyManagerImpl<T>> {
+    hkm: &'a mut M,
+    id: HotkeyId,
+    _phantom: PhantomData<T>,
+}
+
+impl<T, M: HotkeyManagerImpl<T>> HotkeyGuard<'_, T, M> {
+    /// Get the `HotkeyId` of the guarded hotkey.
+    ///
+    pub fn id(&self) -> HotkeyId {
+        self.id
+    }
+}
+
+impl<T, M: HotkeyManagerImpl<T>> Deref for HotkeyGuard<'_, T, M> {
+    type Target = M;
+
+    fn deref(&self) -> &Self::Target {
+        self.hkm
+    }
+}
+
+impl<T, M: HotkeyManagerImpl<T>> DerefMut for HotkeyGuard<'_, T, M> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.hkm
+    }
+}
+
+impl<T, M: HotkeyManagerImpl<T>> Drop for HotkeyGuard<'_, T, M> {
+    fn drop(&mut self) {
+        let _ = self.hkm.unregister(self.id);
+    }
+}
+
+/// The `InterruptHandle` can be used to interrupt the event loop of the originating `HotkeyManager`.
+/// This handle can be used from any thread and can be used multiple times.
+///
+/// # Note
+/// This handle will technically stay valid even after the `HotkeyManager` is dropped, but it will
+/// simply not do anything.
+///
+#[derive(Clone)]
+pub struct InterruptHandle(Arc<dyn Fn() + Send + Sync>);
+
+impl InterruptHandle {
+    /// Create an `InterruptHandle` that executes `interrupt` to interrupt the event loop. This is
+    /// used to implement `backend::HotkeyBackend::interrupt_handle` for custom backends.
+    ///
+    pub fn new(interrupt: impl Fn() + Send + Sync + 'static) -> InterruptHandle {
+        InterruptHandle(Arc::new(interrupt))
+    }
+
+    /// Interrupt the evet loop of the associated `HotkeyManager`.
+    ///
+    pub fn interrupt(&self) {
+        (self.0)()
+    }
+}
+
+/// The `LoopQuitter` can be used to stop the event loop of the originating `HotkeyManager` by
+/// posting a `WM_QUIT` message, which is the usual way of stopping a windows message loop from
+/// another thread. When the `WM_QUIT` message is received, `handle_hotkey` returns `Ok(None)` and
+/// `event_loop` returns.
+///
+/// This handle can be used from any thread and can be used multiple times.
+///
+/// # Windows API Functions used
+/// - <https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-postmessagew>
+/// - <https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-postthreadmessagew>
+///
+#[derive(Clone)]
+pub struct LoopQuitter(Arc<dyn Fn() + Send + Sync>);
+
+impl LoopQuitter {
+    /// Create a `LoopQuitter` that executes `quit` to stop the event loop. This is used to
+    /// implement `backend::HotkeyBackend::quit_handle` for custom backends.
+    ///
+    pub fn new(quit: impl Fn() + Send + Sync + 'static) -> LoopQuitter {
+        LoopQuitter(Arc::new(quit))
+    }
+
+    /// Stop the event loop of the associated `HotkeyManager`.
+    ///
+    pub fn quit(&self) {
+        (self.0)()
+    }
+}
+
+/// Get the global keystate for a given Virtual Key.
+///
+/// Return true if the key is pressed, false otherwise.
+///
+/// On other platforms than windows, false is always returned.
+///
+/// ## Windows API Functions used
+/// - <https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-getasynckeystate>
+///
+pub fn get_global_keystate(vk: VKey) -> bool {
+    is_pressed_state(unsafe { GetAsyncKeyState(vk.to_vk_code()) })
+}
+
+/// Check the pressed bit of a key state from `GetAsyncKeyState` or `GetKeyState`
+///
+fn is_pressed_state(key_state: i16) -> bool {
+    // Most significant bit represents key state (1 => pressed, 0 => not pressed). The state is
+    // signed, so it is checked as `u16` to not depend on the sign extension
+    (key_state as u16 & 0x8000) != 0
+}
+
+/// Check if AltGr is currently held down, using the same key state as `get_global_keystate`.
+///
+/// On layouts with an AltGr key, windows reports the key as RIGHT ALT together with a synthetic
+/// LEFT CTRL, so this checks for both keys. Holding down RIGHT ALT and LEFT CTRL manually can't
+/// be distinguished from AltGr and is detected as well.
+///
+/// On other platforms than windows, false is always returned.
+///
+/// ## Windows API Functions used
+/// - <https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-getasynckeystate>
+///
+pub fn is_altgr_active() -> bool {
+    altgr_pressed(get_global_keystate)
+}
+
+/// Check if AltGr is held down according to the given key state, see `is_altgr_active`
+///
+fn altgr_pressed(key_state: impl Fn(VKey) -> bool) -> bool {
+    key_state(VKey::RMenu) && key_state(VKey::LControl)
+}
+
+/// Get all keys that are currently pressed, using the same key state as `get_global_keystate`.
+/// This can be used to show the keys that the user holds down, for example when capturing a new
+/// hotkey combination or to find stuck modifier keys.
+///
+/// Only the named `VKey` variants are checked (see `VKey::ALL`), in the order of their
+/// declaration. Keys with a generic and a left/right specific variant are reported with both,
+/// for example `VKey::Shift` and `VKey::LShift`.
+///
+/// On other platforms than windows, no key is ever reported as pressed.
+///
+/// ## Windows API Functions used
+/// - <https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-getasynckeystate>
+///
+pub fn get_pressed_keys() -> Vec<VKey> {
+    VKey::ALL
+        .iter()
+        .copied()
+        .filter(|&vk| get_global_keystate(vk))
+        .collect()
+}
+
+/// The interval in which `capture_combination` polls the key state
+const CAPTURE_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Wait for the user to press a key combination and return it, for example to let the user
+/// choose a new hotkey in a settings screen. This is the capture counterpart to `register`.
+///
+/// The combination is complete as soon as a non-modifier key is pressed. The modifier keys that
+/// are held down at that moment become the modifiers of the combination, pressing only modifier
+/// keys never completes it. Keys that are already held down when the capture starts, for example
+/// the key that started the capture, are ignored until they are released.
+///
+/// The key state is polled using `get_pressed_keys`, so the pressed keys still reach the
+/// foreground application. Returns `HkError::Timeout` if no combination was pressed within the
+/// timeout. On other platforms than windows, `HkError::Unsupported` is returned.
+///
+/// ## Windows API Functions used
+/// - <https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-getasynckeystate>
+///
+pub fn capture_combination(timeout: Duration) -> Result<KeyCombination, HkError> {
+    if !cfg!(windows) {
+        return Err(HkError::Unsupported);
+    }
+
+    // If the deadline can't be represented, the timeout is effectively infinite
+    let deadline = Instant::now().checked_add(timeout);
+    let mut ignored = get_pressed_keys();
+
+    loop {
+        let pressed = get_pressed_keys();
+        ignored.retain(|vk| pressed.contains(vk));
+
+        let mut key = None;
+        let mut modifiers = Vec::new();
+        for vk in pressed {
+            match vk.try_into() {
+                Ok(modifier) => {
+                    if !modifiers.contains(&modifier) {
+                        modifiers.push(modifier);
+                    }
+                }
+                Err(()) if key.is_none() && !ignored.contains(&vk) => key = Some(vk),
+                Err(()) => (),
+            }
+        }
+
+        if let Some(key) = key {
+            return Ok(KeyCombination::new(key, &modifiers));
+        }
+        if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+            return Err(HkError::Timeout);
+        }
+
+        thread::sleep(CAPTURE_POLL_INTERVAL);
+    }
+}
+
+/// Check if the given Virtual Key was pressed since the previous query of the key state.
+///
+/// Return true if the key transitioned to the pressed state since the last call to
+/// `GetAsyncKeyState` for that key, false otherwise. This can be used to detect key presses
+/// without a low-level keyboard hook.
+///
+/// # Note
+/// The "was pressed" bit is shared with all other applications that call `GetAsyncKeyState` and
+/// is only reliable when the key state is consistently polled from the same thread.
+///
+/// On other platforms than windows, false is always returned.
+///
+/// ## Windows API Functions used
+/// - <https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-getasynckeystate>
+///
+pub fn get_key_pressed_since(vk: VKey) -> bool {
+    // Least significant bit represents if the key was pressed since the last call
+    let key_state = unsafe { GetAsyncKeyState(vk.to_vk_code()) };
+
+    (key_state & 1) != 0
+}
+
+/// Get the toggle state for a given Virtual Key. This is meant for the lock keys `VKey::Capital`
+/// (CAPS LOCK), `VKey::Numlock` and `VKey::Scroll` (SCROLL LOCK).
+///
+/// Return true if the key is toggled on, false otherwise.
+///
+/// # Note
+/// Unlike `get_global_keystate`, this doesn't report whether the key is currently held down, but
+/// whether the lock is active. For example CAPS LOCK is toggled on after being pressed and
+/// released once, even though it is not pressed anymore. The toggle state is taken from the key
+/// state of the calling thread, which is updated as the thread processes its input messages.
+///
+/// On other platforms than windows, false is always returned.
+///
+/// ## Windows API Functions used
+/// - <https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-getkeystate>
+///
+pub fn get_toggle_state(vk: VKey) -> bool {
+    is_toggled_state(unsafe { GetKeyState(vk.to_vk_code()) })
+}
+
+/// Check the toggle bit of a key state from `GetKeyState`
+///
+fn is_toggled_state(key_state: i16) -> bool {
+    // Least significant bit represents the toggle state (1 => toggled, 0 => not toggled)
+    (key_state & 1) != 0
+}
+
+/// Replacement for `GetAsyncKeyState` on other platforms than windows, no key is ever pressed
+///
+#[cfg(not(windows))]
+#[allow(non_snake_case)]
+unsafe fn GetAsyncKeyState(_vk: i32) -> i16 {
+    0
+}
+
+/// Replacement for `GetKeyState` on other platforms than windows, no key is ever toggled
+///
+#[cfg(not(windows))]
+#[allow(non_snake_case)]
+unsafe fn GetKeyState(_vk: i32) -> i16 {
+    0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::mock::MockBackend;
+
+    #[test]
+    fn pressed_state_checks_the_high_bit() {
+        // The high bit makes the state negative
+        assert!(is_pressed_state(0x8000_u16 as i16));
+        assert!(is_pressed_state(0x8001_u16 as i16));
+        assert!(is_pressed_state(-1));
+
+        assert!(!is_pressed_state(0));
+        assert!(!is_pressed_state(0x0001));
+        assert!(!is_pressed_state(0x7FFF));
+    }
+
+    #[test]
+    fn toggled_state_checks_the_low_bit() {
+        assert!(is_toggled_state(0x0001));
+        assert!(is_toggled_state(0x8001_u16 as i16));
+
+        // A held down lock key that is not toggled on
+        assert!(!is_toggled_state(0x8000_u16 as i16));
+        assert!(!is_toggled_state(0));
+    }
+
+    #[test]
+    fn register_toggle_flips_the_state() {
+        let backend = MockBackend::new();
+        let mut hkm = singlethreaded::HotkeyManager::<bool>::with_backend(backend.clone());
+        let (id, state) = hkm.register_toggle(VKey::A, &[ModKey::Ctrl]).unwrap();
+        assert!(!state.load(Ordering::SeqCst));
+
+        backend.push_hotkey(id);
+        assert_eq!(hkm.try_handle_hotkey(), Some(true));
+        assert!(state.load(Ordering::SeqCst));
+
+        backend.push_hotkey(id);
+        assert_eq!(hkm.try_handle_hotkey(), Some(false));
+        assert!(!state.load(Ordering::SeqCst));
+
+        // The state can also be changed from outside, the next press flips it again
+        state.store(true, Ordering::SeqCst);
+        backend.push_hotkey(id);
+        assert_eq!(hkm.try_handle_hotkey(), Some(false));
+    }
+
+    #[test]
+    fn async_dispatch_sends_the_results_until_unregistered() {
+        let backend = MockBackend::new();
+        let mut hkm = singlethreaded::HotkeyManager::<()>::with_backend(backend.clone());
+        let mut presses = 0;
+        let (id, results) = hkm
+            .register_async_dispatch(VKey::A, &[ModKey::Ctrl], move || {
+                presses += 1;
+                presses
+            })
+            .unwrap();
+
+        backend.push_hotkey(id);
+        assert_eq!(hkm.try_handle_hotkey(), Some(()));
+        backend.push_hotkey(id);
+        assert_eq!(hkm.try_handle_hotkey(), Some(()));
+
+        let timeout = Duration::from_secs(5);
+        assert_eq!(results.recv_timeout(timeout), Ok(1));
+        assert_eq!(results.recv_timeout(timeout), Ok(2));
+
+        // Dropping the callback on unregister disconnects the receiver
+        hkm.unregister(id).unwrap();
+        assert_eq!(
+            results.recv_timeout(timeout),
+            Err(mpsc::RecvTimeoutError::Disconnected)
+        );
+    }
+
+    #[test]
+    fn async_dispatch_shares_one_worker_thread() {
+        let backend = MockBackend::new();
+        let mut hkm = singlethreaded::HotkeyManager::<()>::with_backend(backend.clone());
+        let (id_a, results_a) = hkm
+            .register_async_dispatch(VKey::A, &[ModKey::Ctrl], || thread::current().id())
+            .unwrap();
+        let (id_b, results_b) = hkm
+            .register_async_dispatch(VKey::B, &[ModKey::Ctrl], || -> thread::ThreadId {
+                panic!("callback panicked")
+            })
+            .unwrap();
+        let (id_c, results_c) = hkm
+            .register_async_dispatch(VKey::C, &[ModKey::Ctrl], || thread::current().id())
+            .unwrap();
+
+        for id in [id_a, id_b, id_c] {
+            backend.push_hotkey(id);
+            assert_eq!(hkm.try_handle_hotkey(), Some(()));
+        }
+
+        // The panic of one callback doesn't stop the callbacks of the other hotkeys
+        let timeout = Duration::from_secs(5);
+        let worker = results_a.recv_timeout(timeout).unwrap();
+        assert_eq!(results_c.recv_timeout(timeout), Ok(worker));
+        assert_ne!(worker, thread::current().id());
+
+        // Further presses of the panicked hotkey are ignored
+        backend.push_hotkey(id_b);
+        assert_eq!(hkm.try_handle_hotkey(), Some(()));
+        backend.push_hotkey(id_a);
+        assert_eq!(hkm.try_handle_hotkey(), Some(()));
+        assert_eq!(results_a.recv_timeout(timeout), Ok(worker));
+        assert!(results_b.try_recv().is_err());
+    }
 }
@@ -0,0 +1,30 @@
+//! Internal logging macros. With the `log` feature, the messages are forwarded to the `log`
+//! crate. Otherwise the macros compile to nothing, the arguments are only type checked so they
+//! don't cause unused warnings.
+//!
+
+/// Emit a message at the trace level
+///
+macro_rules! log_trace {
+    ($($arg:tt)*) => {
+        #[cfg(feature = "log")]
+        log::trace!($($arg)*);
+        #[cfg(not(feature = "log"))]
+        if false {
+            let _ = format_args!($($arg)*);
+        }
+    };
+}
+
+/// Emit a message at the debug level
+///
+macro_rules! log_debug {
+    ($($arg:tt)*) => {
+        #[cfg(feature = "log")]
+        log::debug!($($arg)*);
+        #[cfg(not(feature = "log"))]
+        if false {
+            let _ = format_args!($($arg)*);
+        }
+    };
+}
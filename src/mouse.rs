@@ -0,0 +1,61 @@
+//! Mouse buttons as hotkeys. Only available with the `mouse` feature.
+//!
+//! `RegisterHotKey` doesn't support mouse buttons, so the `Win32Backend` installs a low-level
+//! mouse hook (`WH_MOUSE_LL`) on the thread of the `HotkeyManager` while mouse hotkeys are
+//! registered. When a registered button is pressed together with exactly the registered modifier
+//! keys, the hook posts a hotkey event to the `HotkeyManager`. The event is handled like any other
+//! hotkey event, so extra keys, conditions and all other `RegisterOptions` apply to mouse hotkeys
+//! as well. The hook is uninstalled when the last mouse hotkey is unregistered.
+//!
+//! ## Note
+//! Low-level hooks are only called while the thread that installed them is handling messages,
+//! so the `HotkeyManager` needs to run its event loop for the mouse hotkeys to work. The mouse
+//! clicks are not suppressed and still reach the window below the cursor.
+//!
+
+use crate::keys::{winapi_keycodes::*, VKey};
+
+/// A mouse button that can be used as the main key of a hotkey
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MouseButton {
+    /// Left mouse button (`VK_LBUTTON`)
+    Left,
+    /// Right mouse button (`VK_RBUTTON`)
+    Right,
+    /// Middle mouse button (`VK_MBUTTON`)
+    Middle,
+    /// First extra mouse button, usually "back" (`VK_XBUTTON1`)
+    XButton1,
+    /// Second extra mouse button, usually "forward" (`VK_XBUTTON2`)
+    XButton2,
+}
+
+impl MouseButton {
+    /// Get the `VKey` of the mouse button. Hotkeys registered with this key are handled by the
+    /// mouse hook instead of `RegisterHotKey`.
+    ///
+    pub const fn to_vkey(self) -> VKey {
+        let code = match self {
+            MouseButton::Left => VK_LBUTTON,
+            MouseButton::Right => VK_RBUTTON,
+            MouseButton::Middle => VK_MBUTTON,
+            MouseButton::XButton1 => VK_XBUTTON1,
+            MouseButton::XButton2 => VK_XBUTTON2,
+        };
+        VKey::CustomKeyCode(code)
+    }
+
+    /// Get the mouse button for a `VKey`, if the key is a mouse button.
+    ///
+    pub fn from_vkey(key: VKey) -> Option<MouseButton> {
+        Some(match key.to_vk_code() {
+            VK_LBUTTON => MouseButton::Left,
+            VK_RBUTTON => MouseButton::Right,
+            VK_MBUTTON => MouseButton::Middle,
+            VK_XBUTTON1 => MouseButton::XButton1,
+            VK_XBUTTON2 => MouseButton::XButton2,
+            _ => return None,
+        })
+    }
+}
@@ -0,0 +1,452 @@
+//! Multi-step hotkey sequences, also known as chords. For example `CTRL + K` followed by
+//! `CTRL + D`, as used by the keymaps of many editors.
+//!
+//! A `SequenceManager` registers every distinct step of its sequences once as a regular hotkey
+//! and tracks the steps that were pressed so far. The callback of a sequence is executed when
+//! all of its steps were pressed in order, with at most the sequence timeout between two steps.
+//!
+//! ## Overlapping sequences
+//! Multiple sequences can start with the same steps, for example `CTRL + K, CTRL + D` and
+//! `CTRL + K, CTRL + C`. After `CTRL + K` is pressed, both sequences are in progress and the
+//! next step decides which one is completed. A step that doesn't continue any of the sequences
+//! in progress resets the state, but it can start a new sequence itself.
+//!
+//! To keep this unambiguous, a sequence can't be registered if it is a prefix of another
+//! registered sequence or the other way around. Otherwise it would be unclear whether the shorter
+//! sequence should be executed right away or if the state should wait for the longer one.
+//!
+
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    backend::HotkeyBackend,
+    error::HkError,
+    keys::{KeyCombination, ModKey},
+    singlethreaded, HotkeyId, HotkeyManagerImpl, InterruptHandle, LoopQuitter,
+};
+
+/// The default maximum time between two steps of a sequence
+pub const DEFAULT_SEQUENCE_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Identifier of a sequence that was registered with a `SequenceManager`. The id is used to
+/// unregister the sequence.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SequenceId(usize);
+
+/// A registered sequence with its callback
+///
+struct Sequence<T> {
+    steps: Vec<KeyCombination>,
+    callback: Box<dyn FnMut() -> T + 'static>,
+}
+
+/// Manages multi-step hotkey sequences on top of a singlethreaded `HotkeyManager`. See the module
+/// documentation for how the steps are matched.
+///
+/// Like the singlethreaded `HotkeyManager`, the `SequenceManager` can't be moved to other
+/// threads, since the hotkey events are delivered to the thread that registered the hotkeys.
+///
+pub struct SequenceManager<T> {
+    /// The hotkey manager that has the steps registered. The callback of each step returns its
+    /// key combination
+    hkm: singlethreaded::HotkeyManager<KeyCombination>,
+    /// The registered steps with their hotkey id
+    steps: HashMap<KeyCombination, HotkeyId>,
+    /// The registered sequences
+    sequences: HashMap<SequenceId, Sequence<T>>,
+    /// The next id for a registered sequence
+    next_id: usize,
+    /// The maximum time between two steps of a sequence
+    timeout: Duration,
+    /// The steps that were pressed so far
+    pending: Vec<KeyCombination>,
+    /// The time of the last pressed step, if a sequence is in progress
+    last_step: Option<Instant>,
+}
+
+impl<T> Default for SequenceManager<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> SequenceManager<T> {
+    /// Create a new `SequenceManager` that uses the `DEFAULT_SEQUENCE_TIMEOUT`.
+    ///
+    pub fn new() -> SequenceManager<T> {
+        Self::from_manager(singlethreaded::HotkeyManager::new())
+    }
+
+    /// Create a new `SequenceManager` that uses a custom `HotkeyBackend` to register the steps,
+    /// for example to test sequences with synthetic hotkey events.
+    ///
+    pub fn with_backend(backend: impl HotkeyBackend + 'static) -> SequenceManager<T> {
+        Self::from_manager(singlethreaded::HotkeyManager::with_backend(backend))
+    }
+
+    fn from_manager(hkm: singlethreaded::HotkeyManager<KeyCombination>) -> SequenceManager<T> {
+        SequenceManager {
+            hkm,
+            steps: HashMap::new(),
+            sequences: HashMap::new(),
+            next_id: 0,
+            timeout: DEFAULT_SEQUENCE_TIMEOUT,
+            pending: Vec::new(),
+            last_step: None,
+        }
+    }
+
+    /// Get the maximum time between two steps of a sequence.
+    ///
+    pub fn timeout(&self) -> Duration {
+        self.timeout
+    }
+
+    /// Set the maximum time between two steps of a sequence. If the next step is not pressed in
+    /// time, the sequence in progress is reset.
+    ///
+    pub fn set_timeout(&mut self, timeout: Duration) {
+        self.timeout = timeout;
+    }
+
+    /// Register a new sequence. The `callback` is executed when all `steps` are pressed in order.
+    ///
+    /// Steps that are shared with other sequences are only registered once. Returns
+    /// `HkError::EmptySequence` if there are no steps and `HkError::ConflictingSequence` if the
+    /// sequence is a prefix of a registered sequence or the other way around. If registering one
+    /// of the steps fails, the steps that were registered for this sequence are unregistered
+    /// again and the error is returned.
+    ///
+    /// Every step is a hotkey for its main key and modifiers, so two steps can't differ only in
+    /// their extra keys. Such a step is rejected with `HkError::AlreadyRegistered`, which
+    /// contains the id of the conflicting step if it belongs to a registered sequence.
+    ///
+    /// # Windows API Functions used
+    /// - <https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-registerhotkey>
+    ///
+    pub fn register(
+        &mut self,
+        steps: &[KeyCombination],
+        callback: impl FnMut() -> T + 'static,
+    ) -> Result<SequenceId, HkError> {
+        if steps.is_empty() {
+            return Err(HkError::EmptySequence);
+        }
+
+        if let Some(existing) = self.sequences.values().find(|seq| {
+            let len = seq.steps.len().min(steps.len());
+            seq.steps[..len] == steps[..len]
+        }) {
+            return Err(HkError::ConflictingSequence(format_steps(&existing.steps)));
+        }
+
+        // Check the steps before registering any of them, the hotkey manager would reject the
+        // step only when it is registered
+        for (i, step) in steps.iter().enumerate() {
+            let registered = self
+                .steps
+                .iter()
+                .find(|(other, _)| same_hotkey(step, other) && step != *other)
+                .map(|(_, id)| Some(*id));
+            let in_sequence = steps[..i]
+                .iter()
+                .any(|other| same_hotkey(step, other) && step != other)
+                .then_some(None);
+
+            if let Some(id) = registered.or(in_sequence) {
+                return Err(HkError::AlreadyRegistered {
+                    key: step.key,
+                    modifiers: step.modifiers.clone(),
+                    id,
+                });
+            }
+        }
+
+        let mut new_steps = Vec::new();
+        for step in steps {
+            if self.steps.contains_key(step) {
+                continue;
+            }
+
+            let combination = step.clone();
+            match self
+                .hkm
+                .register_combination(step, move || combination.clone())
+            {
+                Ok(id) => {
+                    self.steps.insert(step.clone(), id);
+                    new_steps.push(step.clone());
+                }
+                Err(err) => {
+                    for step in new_steps {
+                        if let Some(id) = self.steps.remove(&step) {
+                            let _ = self.hkm.unregister(id);
+                        }
+                    }
+                    return Err(err);
+                }
+            }
+        }
+
+        let id = SequenceId(self.next_id);
+        self.next_id += 1;
+        self.sequences.insert(
+            id,
+            Sequence {
+                steps: steps.to_vec(),
+                callback: Box::new(callback),
+            },
+        );
+
+        Ok(id)
+    }
+
+    /// Unregister a sequence. Steps that are not used by any other sequence are unregistered as
+    /// well. Unregistering an unknown id does nothing.
+    ///
+    /// If some of the steps fail to unregister, `HkError::Multiple` is returned. The sequence is
+    /// removed regardless.
+    ///
+    /// # Windows API Functions used
+    /// - <https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-unregisterhotkey>
+    ///
+    pub fn unregister(&mut self, id: SequenceId) -> Result<(), HkError> {
+        let Some(sequence) = self.sequences.remove(&id) else {
+            return Ok(());
+        };
+        // The pending steps might belong to the removed sequence
+        self.reset();
+
+        let mut errors = Vec::new();
+        for step in sequence.steps {
+            let in_use = self.sequences.values().any(|seq| seq.steps.contains(&step));
+            if in_use {
+                continue;
+            }
+
+            if let Some(hk_id) = self.steps.remove(&step) {
+                if let Err(err) = self.hkm.unregister(hk_id) {
+                    errors.push((hk_id, err));
+                }
+            }
+        }
+
+        HkError::from_errors(errors)
+    }
+
+    /// Get the number of steps of the sequence in progress that were pressed so far.
+    ///
+    pub fn pending_steps(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Reset the sequence in progress, so the next step has to start a new sequence.
+    ///
+    pub fn reset(&mut self) {
+        self.pending.clear();
+        self.last_step = None;
+    }
+
+    /// Wait until a sequence is completed and execute its callback. Returns the callback result.
+    /// The function call will block until a sequence is completed or the `SequenceManager` is
+    /// interrupted through an `InterruptHandle`, in which case `None` is returned.
+    ///
+    /// A sequence in progress is reset when interrupted or when the next step is not pressed
+    /// within the timeout. A timeout doesn't return from this function, it keeps waiting for the
+    /// next sequence.
+    ///
+    /// # Windows API Functions used
+    /// - See `HotkeyManagerImpl::handle_hotkey` and `HotkeyManagerImpl::handle_hotkey_timeout`
+    ///
+    pub fn handle_sequence(&mut self) -> Result<Option<T>, HkError> {
+        loop {
+            let deadline = self
+                .last_step
+                .and_then(|last| last.checked_add(self.timeout));
+
+            let step = match deadline {
+                // No sequence in progress, wait for the first step without a timeout
+                None => self.hkm.handle_hotkey()?,
+                Some(deadline) => self
+                    .hkm
+                    .handle_hotkey_timeout(deadline.saturating_duration_since(Instant::now()))?,
+            };
+
+            match step {
+                Some(step) => {
+                    if let Some(ret) = self.advance(step, Instant::now()) {
+                        return Ok(Some(ret));
+                    }
+                }
+                // The sequence in progress timed out, keep waiting for the next sequence
+                None if deadline.is_some_and(|deadline| Instant::now() >= deadline) => {
+                    self.reset();
+                }
+                None => {
+                    self.reset();
+                    return Ok(None);
+                }
+            }
+        }
+    }
+
+    /// Run the event loop, wait for sequences and execute their callbacks. The loop only stops
+    /// when the `SequenceManager` is interrupted through an `InterruptHandle` or reading from the
+    /// message queue fails.
+    ///
+    /// # Windows API Functions used
+    /// - See `handle_sequence`
+    ///
+    pub fn event_loop(&mut self) -> Result<(), HkError> {
+        while self.handle_sequence()?.is_some() {}
+
+        Ok(())
+    }
+
+    /// Get an `InterruptHandle` for this `SequenceManager` that can be used to interrupt
+    /// `handle_sequence` and `event_loop`.
+    ///
+    pub fn interrupt_handle(&self) -> InterruptHandle {
+        self.hkm.interrupt_handle()
+    }
+
+    /// Get a `LoopQuitter` for this `SequenceManager` that can be used to stop `handle_sequence`
+    /// and `event_loop`.
+    ///
+    pub fn quit_handle(&self) -> LoopQuitter {
+        self.hkm.quit_handle()
+    }
+
+    /// Process a pressed step and execute the callback of the sequence that is completed by it,
+    /// if any.
+    ///
+    fn advance(&mut self, step: KeyCombination, now: Instant) -> Option<T> {
+        let timed_out = self
+            .last_step
+            .is_some_and(|last| now.duration_since(last) > self.timeout);
+        if timed_out {
+            self.reset();
+        }
+
+        self.pending.push(step);
+        if !self.is_in_progress() {
+            // The step doesn't continue the sequences in progress, but it might start a new one
+            let step = self.pending.pop();
+            self.pending.clear();
+            self.pending.extend(step);
+
+            if !self.is_in_progress() {
+                self.reset();
+                return None;
+            }
+        }
+        self.last_step = Some(now);
+
+        // Conflicting sequences are rejected on registration, so at most one sequence matches
+        let pending = &self.pending;
+        let completed = self
+            .sequences
+            .values_mut()
+            .find(|seq| seq.steps == *pending)?;
+        let ret = (completed.callback)();
+        self.reset();
+
+        Some(ret)
+    }
+
+    /// Check if the pending steps are the start of at least one registered sequence
+    ///
+    fn is_in_progress(&self) -> bool {
+        self.sequences
+            .values()
+            .any(|seq| seq.steps.starts_with(&self.pending))
+    }
+}
+
+/// Check if the steps are registered as the same hotkey, which only depends on the main key and
+/// the modifiers
+///
+fn same_hotkey(step: &KeyCombination, other: &KeyCombination) -> bool {
+    step.key == other.key && ModKey::combine(&step.modifiers) == ModKey::combine(&other.modifiers)
+}
+
+/// Format the steps of a sequence like `"Ctrl+K, Ctrl+D"`
+///
+fn format_steps(steps: &[KeyCombination]) -> String {
+    steps
+        .iter()
+        .map(|step| step.to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{backend::mock::MockBackend, keys::VKey};
+
+    fn step(key: VKey) -> KeyCombination {
+        KeyCombination::new(key, &[ModKey::Ctrl])
+    }
+
+    #[test]
+    fn shared_steps_are_registered_once() {
+        let backend = MockBackend::new();
+        let mut sm = SequenceManager::with_backend(backend.clone());
+
+        sm.register(&[step(VKey::K), step(VKey::D)], || 1).unwrap();
+        sm.register(&[step(VKey::K), step(VKey::C)], || 2).unwrap();
+
+        assert_eq!(backend.registered_ids().len(), 3);
+    }
+
+    #[test]
+    fn steps_that_differ_only_in_extra_keys_are_rejected() {
+        let backend = MockBackend::new();
+        let mut sm = SequenceManager::with_backend(backend.clone());
+        sm.register(&[step(VKey::K), step(VKey::D)], || 1).unwrap();
+        let registered = backend.registered_ids();
+
+        let with_extra_key = step(VKey::K).with_extra_keys(&[VKey::LShift]);
+        match sm.register(&[with_extra_key, step(VKey::C)], || 2) {
+            Err(HkError::AlreadyRegistered { key, id, .. }) => {
+                assert_eq!(key, VKey::K);
+                assert!(id.is_some_and(|id| registered.contains(&id)));
+            }
+            other => panic!("unexpected result {:?}", other.map(|_| ())),
+        }
+
+        // Within the same sequence as well
+        let with_extra_key = step(VKey::X).with_extra_keys(&[VKey::LShift]);
+        assert!(matches!(
+            sm.register(&[step(VKey::X), with_extra_key], || 3),
+            Err(HkError::AlreadyRegistered { id: None, .. })
+        ));
+
+        // Nothing was registered for the rejected sequences
+        assert_eq!(backend.registered_ids(), registered);
+    }
+
+    #[test]
+    fn failed_registration_rolls_back_the_new_steps() {
+        let backend = MockBackend::new();
+        let mut sm = SequenceManager::with_backend(backend.clone());
+        sm.register(&[step(VKey::K), step(VKey::D)], || 1).unwrap();
+        let registered = backend.registered_ids();
+        backend.fail_register(VKey::C, true);
+
+        let res = sm.register(&[step(VKey::K), step(VKey::X), step(VKey::C)], || 2);
+        assert!(matches!(res, Err(HkError::RegistrationFailed(_))));
+
+        // The shared step stays registered, the step that was new is unregistered
+        assert_eq!(backend.registered_ids(), registered);
+
+        backend.fail_register(VKey::C, false);
+        sm.register(&[step(VKey::K), step(VKey::X), step(VKey::C)], || 2)
+            .unwrap();
+    }
+}
@@ -0,0 +1,172 @@
+//! Synthesize key presses to trigger registered hotkeys through the operating system, for example
+//! to verify the hotkey handling end to end in integration tests. Only available with the
+//! `simulate` feature.
+//!
+
+#[cfg(windows)]
+use winapi::um::errhandlingapi::GetLastError;
+#[cfg(windows)]
+use winapi::um::winuser::{SendInput, INPUT, INPUT_KEYBOARD, KEYEVENTF_KEYUP};
+
+use crate::{
+    error::HkError,
+    keys::{ModKey, VKey},
+};
+
+/// Simulate pressing the hotkey combination. The modifiers are pressed in the given order, then
+/// the key is pressed and released and finally the modifiers are released in reverse order.
+///
+/// The inputs are inserted into the input stream of the system like real keyboard input, so a
+/// registered hotkey with the same combination is triggered and its event is received by the
+/// `HotkeyManager` event loop.
+///
+/// ## Note
+/// The key presses are also visible to the foreground window. `SendInput` is subject to UIPI,
+/// so simulating input fails if the foreground window is running with higher privileges.
+///
+/// On other platforms than windows, `HkError::Unsupported` is always returned.
+///
+/// ## Windows API Functions used
+/// - <https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-sendinput>
+///
+pub fn simulate_hotkey(key: VKey, key_modifiers: &[ModKey]) -> Result<(), HkError> {
+    let mod_keys: Vec<VKey> = key_modifiers.iter().map(modifier_vkey).collect();
+
+    let mut events = Vec::with_capacity(mod_keys.len() * 2 + 2);
+    events.extend(mod_keys.iter().map(|vk| (*vk, false)));
+    events.push((key, false));
+    events.push((key, true));
+    events.extend(mod_keys.iter().rev().map(|vk| (*vk, true)));
+
+    send_key_events(&events)
+}
+
+/// Get the virtual key that is pressed for the modifier key
+///
+fn modifier_vkey(modifier: &ModKey) -> VKey {
+    match modifier {
+        ModKey::Alt => VKey::Menu,
+        ModKey::Ctrl => VKey::Control,
+        ModKey::Shift => VKey::Shift,
+        ModKey::Win => VKey::LWin,
+    }
+}
+
+/// Send the key events using `SendInput`. Each event is the key and whether it is released.
+///
+/// If not all events were sent, the keys that were already pressed are released again, so they
+/// don't stay stuck in the pressed state.
+///
+#[cfg(windows)]
+fn send_key_events(events: &[(VKey, bool)]) -> Result<(), HkError> {
+    let mut inputs: Vec<INPUT> = events
+        .iter()
+        .map(|(vk, release)| key_input(*vk, *release))
+        .collect();
+
+    let sent = send_inputs(&mut inputs);
+
+    // Returns the number of inserted events, which is less than requested if the input was
+    // blocked by another thread or by UIPI
+    if sent != inputs.len() {
+        let err = HkError::Os(unsafe { GetLastError() });
+
+        // Releasing the keys is best effort, the input is most likely still blocked
+        let mut releases: Vec<INPUT> = pressed_keys(&events[..sent])
+            .into_iter()
+            .map(|vk| key_input(vk, true))
+            .collect();
+        if !releases.is_empty() {
+            send_inputs(&mut releases);
+        }
+
+        return Err(err);
+    }
+
+    Ok(())
+}
+
+/// Create the `INPUT` for pressing or releasing the key.
+///
+#[cfg(windows)]
+fn key_input(vk: VKey, release: bool) -> INPUT {
+    let mut input: INPUT = unsafe { std::mem::zeroed() };
+    input.type_ = INPUT_KEYBOARD;
+    let ki = unsafe { input.u.ki_mut() };
+    ki.wVk = vk.to_vk_code() as u16;
+    if release {
+        ki.dwFlags = KEYEVENTF_KEYUP;
+    }
+    input
+}
+
+/// Insert the inputs using `SendInput` and return the number of inserted inputs.
+///
+#[cfg(windows)]
+fn send_inputs(inputs: &mut [INPUT]) -> usize {
+    let sent = unsafe {
+        SendInput(
+            inputs.len() as u32,
+            inputs.as_mut_ptr(),
+            std::mem::size_of::<INPUT>() as i32,
+        )
+    };
+
+    sent as usize
+}
+
+/// Get the keys that are still pressed after the key events, in the reverse order of being
+/// pressed.
+///
+#[cfg_attr(not(windows), allow(dead_code))]
+fn pressed_keys(events: &[(VKey, bool)]) -> Vec<VKey> {
+    let mut pressed: Vec<VKey> = Vec::new();
+    for (vk, release) in events {
+        if *release {
+            pressed.retain(|key| key != vk);
+        } else if !pressed.contains(vk) {
+            pressed.push(*vk);
+        }
+    }
+    pressed.reverse();
+
+    pressed
+}
+
+#[cfg(not(windows))]
+fn send_key_events(_events: &[(VKey, bool)]) -> Result<(), HkError> {
+    Err(HkError::Unsupported)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pressed_keys_are_released_in_reverse_order() {
+        let events = [
+            (VKey::Control, false),
+            (VKey::Shift, false),
+            (VKey::A, false),
+        ];
+
+        assert_eq!(
+            pressed_keys(&events),
+            vec![VKey::A, VKey::Shift, VKey::Control]
+        );
+    }
+
+    #[test]
+    fn released_keys_are_not_pressed() {
+        let events = [
+            (VKey::Control, false),
+            (VKey::A, false),
+            (VKey::A, true),
+            (VKey::Control, true),
+        ];
+
+        assert_eq!(pressed_keys(&events[..3]), vec![VKey::Control]);
+        assert!(pressed_keys(&events).is_empty());
+        assert!(pressed_keys(&[]).is_empty());
+    }
+}
@@ -1,21 +1,30 @@
-#[cfg(not(target_os = "windows"))]
-compile_error!("Only supported on windows");
-
+use std::cell::{Cell, RefCell};
 use std::collections::HashMap;
 use std::marker::PhantomData;
-
-use winapi::shared::windef::HWND;
-use winapi::um::libloaderapi::GetModuleHandleA;
-use winapi::um::winuser::{
-    self, CreateWindowExA, DestroyWindow, GetMessageW, RegisterHotKey, UnregisterHotKey,
-    HWND_MESSAGE, MSG, WM_HOTKEY, WM_NULL, WS_DISABLED, WS_EX_NOACTIVATE,
-};
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::{Mutex, MutexGuard, PoisonError};
+use std::time::{Duration, Instant};
 
 use crate::{
-    error::HkError, get_global_keystate, keys::*, HotkeyCallback, HotkeyId, HotkeyManagerImpl,
-    InterruptHandle,
+    backend::{self, BackendMessage, HotkeyBackend, HotkeyTrigger},
+    config::BindingDescriptor,
+    error::HkError,
+    keys::*,
+    HotkeyCallback, HotkeyContext, HotkeyEvent, HotkeyId, HotkeyInfo, HotkeyManagerImpl,
+    InterruptHandle, LoopQuitter, RegisterOptions,
 };
 
+/// The id that `can_register` uses to check a combination. It is never handed out for a hotkey
+const SCRATCH_ID: HotkeyId = HotkeyId(i32::MIN);
+
+/// The next id of the process-global id pool that is shared by all HotkeyManagers created with
+/// `new`. Counted as `i64`, so `i32::MAX` can be handed out as the last id
+static NEXT_SHARED_ID: AtomicI64 = AtomicI64::new(0);
+/// Ids of the process-global id pool that were released and are handed out again before
+/// advancing `NEXT_SHARED_ID`
+static FREE_SHARED_IDS: Mutex<Vec<i32>> = Mutex::new(Vec::new());
+
 /// The HotkeyManager is used to register, unregister and await hotkeys with their callback
 /// functions.
 ///
@@ -24,10 +33,15 @@ use crate::{
 /// threads.
 ///
 pub struct HotkeyManager<T> {
-    /// Handle to the hidden window that is used to receive the hotkey events
-    hwnd: HwndDropper,
-    id_offset: i32,
+    /// The backend that is used to register the hotkeys and receive the hotkey events
+    backend: Box<dyn HotkeyBackend>,
+    /// Hands out the `HotkeyId`s for new hotkeys
+    ids: IdAllocator,
     handlers: HashMap<HotkeyId, HotkeyCallback<T>>,
+    /// Whether all hotkeys are unregistered with windows by `pause_all`
+    paused: bool,
+    /// The callback for hotkey events without a registered hotkey, see `set_fallback`
+    fallback: RefCell<Option<FallbackCallback<T>>>,
 
     /// Make sure that `HotkeyManager` is not Send / Sync. This prevents it from being moved
     /// between threads, which would prevent hotkey-events from being received.
@@ -46,123 +60,804 @@ impl<T> HotkeyManagerImpl<T> for HotkeyManager<T> {
     /// Create a new HotkeyManager instance. This instance can't be moved to other threads due to
     /// limitations in the windows events system.
     ///
+    /// On other platforms than windows, registering hotkeys with this instance fails with
+    /// `HkError::Unsupported`.
+    ///
     fn new() -> HotkeyManager<T> {
-        // Try to create a hidden window to receive the hotkey events for the HotkeyManager.
-        // If the window creation fails, HWND 0 (null) is used which registers hotkeys to the thread
-        // message queue and gets messages from all thread associated windows
-        let hwnd = create_hidden_window().unwrap_or(HwndDropper(std::ptr::null_mut()));
-        HotkeyManager {
-            hwnd,
-            id_offset: 0,
-            handlers: HashMap::new(),
-            _unimpl_send_sync: PhantomData,
-        }
+        Self::from_backend(backend::default_backend(), IdAllocator::Shared)
+    }
+
+    /// Same as `new`, but start handing out `HotkeyId`s from `id_offset`. The ids are counted by
+    /// this HotkeyManager instead of the process-global id pool.
+    ///
+    fn new_with_id_offset(id_offset: i32) -> HotkeyManager<T> {
+        Self::from_backend(backend::default_backend(), IdAllocator::local(id_offset))
     }
 
     fn register_extrakeys(
         &mut self,
         key: VKey,
-        key_modifiers: &[ModKey],
+        key_modifiers: impl Into<Modifiers>,
         extra_keys: &[VKey],
         callback: impl Fn() -> T + Send + 'static,
     ) -> Result<HotkeyId, HkError> {
-        let register_id = HotkeyId(self.id_offset);
-        self.id_offset += 1;
-
-        // Try to register the hotkey combination with windows
-        let reg_ok = unsafe {
-            RegisterHotKey(
-                self.hwnd.0,
-                register_id.0,
-                ModKey::combine(key_modifiers) | winuser::MOD_NOREPEAT as u32,
-                key.to_vk_code() as u32,
-            )
-        };
+        self.register_extrakeys_opts(
+            key,
+            &key_modifiers.into().to_vec(),
+            extra_keys,
+            RegisterOptions::default(),
+            callback,
+        )
+    }
 
-        if reg_ok == 0 {
-            Err(HkError::RegistrationFailed)
-        } else {
-            // Add the HotkeyCallback to the handlers when the hotkey was registered
-            self.handlers.insert(
-                register_id,
-                HotkeyCallback {
-                    callback: Box::new(callback),
-                    extra_keys: extra_keys.to_owned(),
-                },
-            );
+    fn register_ctx_opts(
+        &mut self,
+        key: VKey,
+        key_modifiers: &[ModKey],
+        extra_keys: &[VKey],
+        options: RegisterOptions,
+        callback: impl FnMut(&HotkeyContext) -> T + Send + 'static,
+    ) -> Result<HotkeyId, HkError> {
+        self.check_thread()?;
+
+        // Check the own hotkeys first, so that the error contains the id of the existing hotkey.
+        // This also catches duplicates while paused, when windows doesn't know the hotkeys
+        if let Some(id) = self.find_combo(key, key_modifiers) {
+            return Err(HkError::AlreadyRegistered {
+                key,
+                modifiers: key_modifiers.to_vec(),
+                id: Some(id),
+            });
+        }
+
+        let register_id = self.ids.allocate()?;
 
-            Ok(register_id)
+        // While paused, the hotkey is only registered with windows by `resume_all`
+        if !self.paused {
+            if let Err(err) = self.register_with_backend(register_id, key, key_modifiers, &options)
+            {
+                log_debug!(
+                    "failed to register {}: {}",
+                    KeyCombination::new(key, key_modifiers).with_extra_keys(extra_keys),
+                    err
+                );
+
+                // The id can be reused, since it was never handed out
+                self.ids.release(register_id);
+                return Err(err);
+            }
         }
+
+        // Add the HotkeyCallback to the handlers when the hotkey was registered
+        self.handlers.insert(
+            register_id,
+            HotkeyCallback {
+                callback: RefCell::new(Box::new(callback)),
+                key,
+                key_modifiers: key_modifiers.to_vec(),
+                extra_keys: extra_keys.to_owned(),
+                options,
+                enabled: true,
+                last_press: Cell::new(None),
+                last_fired: Cell::new(None),
+                fire_count: Cell::new(0),
+            },
+        );
+
+        log_debug!(
+            "registered {:?} ({})",
+            register_id,
+            self.handlers[&register_id].combination()
+        );
+
+        Ok(register_id)
     }
 
     fn register(
         &mut self,
         key: VKey,
-        key_modifiers: &[ModKey],
+        key_modifiers: impl Into<Modifiers>,
         callback: impl Fn() -> T + Send + 'static,
     ) -> Result<HotkeyId, HkError> {
         self.register_extrakeys(key, key_modifiers, &[], callback)
     }
 
+    fn replace_callback(
+        &mut self,
+        id: HotkeyId,
+        mut callback: impl FnMut() -> T + Send + 'static,
+    ) -> Result<(), HkError> {
+        let handler = self.handlers.get_mut(&id).ok_or(HkError::UnknownId(id))?;
+        handler.callback = RefCell::new(Box::new(move |_| callback()));
+
+        Ok(())
+    }
+
+    fn set_fallback(&mut self, callback: impl FnMut(HotkeyId) -> T + Send + 'static) {
+        self.fallback = RefCell::new(Some(Box::new(callback)));
+    }
+
+    fn rebind(
+        &mut self,
+        id: HotkeyId,
+        key: VKey,
+        key_modifiers: impl Into<Modifiers>,
+    ) -> Result<(), HkError> {
+        self.check_thread()?;
+
+        let key_modifiers = &key_modifiers.into().to_vec();
+
+        let handler = self.handlers.get(&id).ok_or(HkError::UnknownId(id))?;
+
+        if let Some(existing) = self.find_combo(key, key_modifiers) {
+            if existing != id {
+                return Err(HkError::AlreadyRegistered {
+                    key,
+                    modifiers: key_modifiers.to_vec(),
+                    id: Some(existing),
+                });
+            }
+        }
+
+        // Inactive hotkeys are not registered with windows, so only the combination is updated
+        if self.is_active(handler) {
+            self.backend.unregister_hotkey(id)?;
+
+            if let Err(err) = self.register_with_backend(id, key, key_modifiers, &handler.options) {
+                // Roll back to the previous combination, which was registered just before
+                let rollback = self.register_with_backend(
+                    id,
+                    handler.key,
+                    &handler.key_modifiers,
+                    &handler.options,
+                );
+
+                if let Err(rollback_err) = rollback {
+                    log_debug!(
+                        "failed to register the previous combination of {:?} again: {}",
+                        id,
+                        rollback_err
+                    );
+
+                    // The hotkey isn't registered with windows anymore, enabling it registers
+                    // the previous combination again
+                    let handler = self.handlers.get_mut(&id).ok_or(HkError::UnknownId(id))?;
+                    handler.enabled = false;
+
+                    return Err(HkError::RollbackFailed {
+                        id,
+                        error: Box::new(err),
+                    });
+                }
+
+                return Err(err);
+            }
+        }
+
+        let handler = self.handlers.get_mut(&id).ok_or(HkError::UnknownId(id))?;
+        handler.key = key;
+        handler.key_modifiers = key_modifiers.to_vec();
+
+        Ok(())
+    }
+
+    fn register_replacing(
+        &mut self,
+        key: VKey,
+        key_modifiers: &[ModKey],
+        callback: impl Fn() -> T + Send + 'static,
+    ) -> Result<HotkeyId, HkError> {
+        self.check_thread()?;
+
+        let Some(old_id) = self.find_combo(key, key_modifiers) else {
+            return self.register(key, key_modifiers, callback);
+        };
+
+        // Keep the replaced hotkey until the new one is registered, so it can be restored
+        let mut old = self
+            .handlers
+            .remove(&old_id)
+            .ok_or(HkError::UnknownId(old_id))?;
+        let was_active = self.is_active(&old);
+        if was_active {
+            if let Err(err) = self.backend.unregister_hotkey(old_id) {
+                self.handlers.insert(old_id, old);
+                return Err(err);
+            }
+        }
+
+        match self.register(key, key_modifiers, callback) {
+            Ok(id) => {
+                log_debug!(
+                    "replaced {:?} ({}) with {:?}",
+                    old_id,
+                    old.combination(),
+                    id
+                );
+                self.ids.release(old_id);
+                Ok(id)
+            }
+            Err(err) => {
+                let mut result = Err(err);
+                if was_active {
+                    if let Err(rollback_err) = self.register_with_backend(
+                        old_id,
+                        old.key,
+                        &old.key_modifiers,
+                        &old.options,
+                    ) {
+                        log_debug!(
+                            "failed to register the replaced {:?} again: {}",
+                            old_id,
+                            rollback_err
+                        );
+
+                        // Enabling the hotkey registers it again
+                        old.enabled = false;
+                        result = result.map_err(|err| HkError::RollbackFailed {
+                            id: old_id,
+                            error: Box::new(err),
+                        });
+                    }
+                }
+                self.handlers.insert(old_id, old);
+
+                result
+            }
+        }
+    }
+
+    fn disable(&mut self, id: HotkeyId) -> Result<(), HkError> {
+        self.check_thread()?;
+
+        let handler = self.handlers.get(&id).ok_or(HkError::UnknownId(id))?;
+        if !handler.enabled {
+            return Ok(());
+        }
+
+        if !self.paused {
+            self.backend.unregister_hotkey(id)?;
+        }
+
+        let handler = self.handlers.get_mut(&id).ok_or(HkError::UnknownId(id))?;
+        handler.enabled = false;
+
+        Ok(())
+    }
+
+    fn enable(&mut self, id: HotkeyId) -> Result<(), HkError> {
+        self.check_thread()?;
+
+        let handler = self.handlers.get(&id).ok_or(HkError::UnknownId(id))?;
+        if handler.enabled {
+            return Ok(());
+        }
+
+        if !self.paused {
+            self.register_with_backend(id, handler.key, &handler.key_modifiers, &handler.options)?;
+        }
+
+        let handler = self.handlers.get_mut(&id).ok_or(HkError::UnknownId(id))?;
+        handler.enabled = true;
+
+        Ok(())
+    }
+
+    fn trigger(&self, id: HotkeyId) -> Result<(), HkError> {
+        self.check_thread()?;
+
+        let handler = self.handlers.get(&id).ok_or(HkError::UnknownId(id))?;
+        if !self.is_active(handler) {
+            return Err(HkError::Inactive(id));
+        }
+
+        let trigger = HotkeyTrigger::new(handler.key, &handler.key_modifiers);
+        self.backend.post_hotkey(id, trigger)
+    }
+
     fn unregister(&mut self, id: HotkeyId) -> Result<(), HkError> {
-        let ok = unsafe { UnregisterHotKey(self.hwnd.0, id.0) };
+        self.check_thread()?;
+
+        // Inactive hotkeys are already unregistered with windows
+        if !matches!(self.handlers.get(&id), Some(handler) if !self.is_active(handler)) {
+            self.backend.unregister_hotkey(id)?;
+        }
+        if let Some(handler) = self.handlers.remove(&id) {
+            log_debug!("unregistered {:?} ({})", id, handler.combination());
+            self.ids.release(id);
+        }
+
+        Ok(())
+    }
 
-        match ok {
-            0 => Err(HkError::UnregistrationFailed),
-            _ => {
-                self.handlers.remove(&id);
-                Ok(())
+    fn pause_all(&mut self) -> Result<(), HkError> {
+        self.check_thread()?;
+
+        if self.paused {
+            return Ok(());
+        }
+
+        // Hotkeys that fail to unregister are not triggered either, since paused hotkeys are
+        // skipped when handling hotkey events
+        let mut errors = Vec::new();
+        for id in self.registered_ids() {
+            if self.handlers[&id].enabled {
+                if let Err(err) = self.backend.unregister_hotkey(id) {
+                    errors.push((id, err));
+                }
             }
         }
+        self.paused = true;
+
+        HkError::from_errors(errors)
     }
 
-    fn unregister_all(&mut self) -> Result<(), HkError> {
-        let ids: Vec<_> = self.handlers.keys().copied().collect();
-        for id in ids {
-            self.unregister(id)?;
+    fn resume_all(&mut self) -> Result<(), HkError> {
+        self.check_thread()?;
+
+        if !self.paused {
+            return Ok(());
+        }
+
+        self.paused = false;
+        let mut errors = Vec::new();
+        for id in self.registered_ids() {
+            let handler = &self.handlers[&id];
+            if !handler.enabled {
+                continue;
+            }
+
+            if let Err(err) = self.register_with_backend(
+                id,
+                handler.key,
+                &handler.key_modifiers,
+                &handler.options,
+            ) {
+                // Keep the hotkey disabled, so it can be retried using `enable`
+                if let Some(handler) = self.handlers.get_mut(&id) {
+                    handler.enabled = false;
+                }
+                errors.push((id, err));
+            }
+        }
+
+        HkError::from_errors(errors)
+    }
+
+    fn unregister_combo(&mut self, key: VKey, key_modifiers: &[ModKey]) -> Result<(), HkError> {
+        match self.find_combo(key, key_modifiers) {
+            Some(id) => self.unregister(id),
+            None => Err(HkError::NotRegistered {
+                key,
+                modifiers: key_modifiers.to_vec(),
+            }),
         }
+    }
+
+    fn unregister_all(&mut self) -> Result<(), HkError> {
+        // Fail once instead of for every hotkey
+        self.check_thread()?;
+
+        // Attempt every hotkey, so the handlers only keep the hotkeys that are still registered
+        let ids = self.registered_ids();
+        self.unregister_many(&ids)
+    }
+
+    fn clear(&mut self) -> Result<(), HkError> {
+        self.unregister_all()?;
+        self.ids.reset();
 
         Ok(())
     }
 
-    fn handle_hotkey(&self) -> Option<T> {
+    fn registered_ids(&self) -> Vec<HotkeyId> {
+        let mut ids: Vec<_> = self.handlers.keys().copied().collect();
+        ids.sort_by_key(|id| id.0);
+
+        ids
+    }
+
+    fn registered(&self) -> Vec<HotkeyInfo> {
+        let mut hotkeys: Vec<_> = self
+            .handlers
+            .iter()
+            .map(|(id, handler)| handler.info(*id))
+            .collect();
+        hotkeys.sort_by_key(|info| info.id.0);
+
+        hotkeys
+    }
+
+    fn dump_config(&self) -> Vec<BindingDescriptor> {
+        let mut ids: Vec<_> = self.handlers.keys().copied().collect();
+        ids.sort_by_key(|id| id.0);
+
+        ids.iter()
+            .map(|id| self.handlers[id].descriptor())
+            .collect()
+    }
+
+    fn is_registered(&self, key: VKey, key_modifiers: &[ModKey]) -> bool {
+        self.find_combo(key, key_modifiers).is_some()
+    }
+
+    fn can_register(&self, key: VKey, key_modifiers: &[ModKey]) -> Result<(), HkError> {
+        self.check_thread()?;
+
+        if let Some(id) = self.find_combo(key, key_modifiers) {
+            return Err(HkError::AlreadyRegistered {
+                key,
+                modifiers: key_modifiers.to_vec(),
+                id: Some(id),
+            });
+        }
+
+        self.backend
+            .register_hotkey(SCRATCH_ID, key, key_modifiers, false)?;
+        self.backend.unregister_hotkey(SCRATCH_ID)
+    }
+
+    fn label(&self, id: HotkeyId) -> Option<String> {
+        self.handlers
+            .get(&id)
+            .and_then(|handler| handler.options.label.clone())
+    }
+
+    fn is_enabled(&self, id: HotkeyId) -> Option<bool> {
+        self.handlers.get(&id).map(|handler| handler.enabled)
+    }
+
+    fn find_by_label(&self, label: &str) -> Option<HotkeyId> {
+        self.handlers
+            .iter()
+            .filter(|(_, handler)| handler.options.label.as_deref() == Some(label))
+            .map(|(id, _)| *id)
+            .min_by_key(|id| id.0)
+    }
+
+    fn len(&self) -> usize {
+        self.handlers.len()
+    }
+
+    fn fire_count(&self, id: HotkeyId) -> Option<u64> {
+        self.handlers
+            .get(&id)
+            .map(|handler| handler.fire_count.get())
+    }
+
+    fn fire_counts(&self) -> HashMap<HotkeyId, u64> {
+        self.handlers
+            .iter()
+            .map(|(id, handler)| (*id, handler.fire_count.get()))
+            .collect()
+    }
+
+    fn handle_hotkey(&self) -> Result<Option<T>, HkError> {
+        Ok(self.handle_hotkey_with_id()?.map(|(_, ret)| ret))
+    }
+
+    fn handle_hotkey_detailed(&self) -> Result<Option<HotkeyEvent<T>>, HkError> {
+        self.check_thread()?;
+
         loop {
-            let mut msg = std::mem::MaybeUninit::<MSG>::uninit();
-
-            // Block and read a message from the message queue. Filtered to receive messages from
-            // WM_NULL to WM_HOTKEY
-            let ok = unsafe { GetMessageW(msg.as_mut_ptr(), self.hwnd.0, WM_NULL, WM_HOTKEY) };
-
-            if ok != 0 {
-                let msg = unsafe { msg.assume_init() };
-
-                if WM_HOTKEY == msg.message {
-                    let hk_id = HotkeyId(msg.wParam as i32);
-
-                    // Get the callback for the received ID
-                    if let Some(handler) = self.handlers.get(&hk_id) {
-                        // Check if all extra keys are pressed
-                        if !handler
-                            .extra_keys
-                            .iter()
-                            .any(|vk| !get_global_keystate(*vk))
-                        {
-                            return Some((handler.callback)());
-                        }
-                    }
-                } else if WM_NULL == msg.message {
-                    return None;
+            match self.dispatch(self.backend.get_message()?) {
+                Dispatch::Callback(event) => return Ok(Some(event)),
+                Dispatch::Panicked(id) => return Err(HkError::CallbackPanicked(id)),
+                Dispatch::Interrupt => return Ok(None),
+                Dispatch::Ignored => (),
+            }
+        }
+    }
+
+    fn handle_hotkey_timeout(&self, timeout: Duration) -> Result<Option<T>, HkError> {
+        self.check_thread()?;
+
+        // If the deadline can't be represented, the timeout is effectively infinite
+        let deadline = Instant::now().checked_add(timeout);
+
+        loop {
+            // Process all messages that are already in the queue before waiting for new ones
+            while let Some(msg) = self.backend.peek_message(false) {
+                match self.dispatch(msg) {
+                    Dispatch::Callback(event) => return Ok(Some(event.result)),
+                    Dispatch::Panicked(id) => return Err(HkError::CallbackPanicked(id)),
+                    Dispatch::Interrupt => return Ok(None),
+                    Dispatch::Ignored => (),
                 }
             }
+
+            let remaining = match deadline {
+                Some(deadline) => deadline.saturating_duration_since(Instant::now()),
+                None => Duration::MAX,
+            };
+            if remaining.is_zero() || !self.backend.wait_message(remaining)? {
+                return Ok(None);
+            }
+        }
+    }
+
+    fn try_handle_hotkey(&self) -> Option<T> {
+        match self.peek_hotkey()? {
+            Dispatch::Callback(event) => Some(event.result),
+            Dispatch::Panicked(_) | Dispatch::Interrupt | Dispatch::Ignored => None,
+        }
+    }
+
+    fn drain_hotkeys(&self) -> Vec<T> {
+        let mut results = Vec::new();
+        while let Some(dispatch) = self.peek_hotkey() {
+            if let Dispatch::Callback(event) = dispatch {
+                results.push(event.result);
+            }
+        }
+
+        results
+    }
+
+    fn event_loop(&self) -> Result<(), HkError> {
+        loop {
+            match self.handle_hotkey() {
+                // A panicking callback doesn't stop the event loop
+                Ok(Some(_)) | Err(HkError::CallbackPanicked(_)) => (),
+                Ok(None) => return Ok(()),
+                Err(err) => return Err(err),
+            }
         }
     }
 
-    fn event_loop(&self) {
-        while self.handle_hotkey().is_some() {}
+    fn owner_thread_id(&self) -> Option<u32> {
+        self.backend.owner_thread_id()
     }
 
     fn interrupt_handle(&self) -> InterruptHandle {
-        InterruptHandle(self.hwnd.0)
+        self.backend.interrupt_handle()
+    }
+
+    fn quit_handle(&self) -> LoopQuitter {
+        self.backend.quit_handle()
+    }
+}
+
+impl<T> HotkeyManager<T> {
+    /// Create a new HotkeyManager that uses a custom `HotkeyBackend` instead of the windows API,
+    /// for example to test the hotkey handling with synthetic hotkey events.
+    ///
+    /// The `HotkeyId`s are counted by this HotkeyManager starting from 0, so they are the same
+    /// in every run.
+    ///
+    pub fn with_backend(backend: impl HotkeyBackend + 'static) -> HotkeyManager<T> {
+        Self::from_backend(Box::new(backend), IdAllocator::local(0))
+    }
+
+    /// Get the extra keys of the hotkey with the given id, for example to show them when editing
+    /// the hotkey. Returns `None` if there is no hotkey with the given id.
+    ///
+    /// # Note
+    /// This is only available for the singlethreaded `HotkeyManager`, since the threadsafe
+    /// `HotkeyManager` can't lend out data from its backend thread. There, the extra keys are
+    /// part of the `HotkeyInfo` returned by `registered`.
+    ///
+    pub fn extra_keys(&self, id: HotkeyId) -> Option<&[VKey]> {
+        self.handlers
+            .get(&id)
+            .map(|handler| handler.extra_keys.as_slice())
+    }
+
+    /// Create a new HotkeyManager with the given backend that hands out `HotkeyId`s from the
+    /// given allocator.
+    ///
+    fn from_backend(backend: Box<dyn HotkeyBackend>, ids: IdAllocator) -> HotkeyManager<T> {
+        HotkeyManager {
+            backend,
+            ids,
+            handlers: HashMap::new(),
+            paused: false,
+            fallback: RefCell::new(None),
+            _unimpl_send_sync: PhantomData,
+        }
+    }
+
+    /// Register the hotkey combination with the backend using the given id. Suppressing hotkeys
+    /// are registered through the keyboard hook of the backend.
+    ///
+    fn register_with_backend(
+        &self,
+        id: HotkeyId,
+        key: VKey,
+        key_modifiers: &[ModKey],
+        options: &RegisterOptions,
+    ) -> Result<(), HkError> {
+        #[cfg(feature = "hook")]
+        if options.suppress {
+            return self.backend.register_suppressing_hotkey(
+                id,
+                key,
+                key_modifiers,
+                options.allow_repeat,
+            );
+        }
+
+        self.backend
+            .register_hotkey(id, key, key_modifiers, options.allow_repeat)
+    }
+
+    /// Check that the HotkeyManager is used on the thread that owns its hotkeys. Otherwise the
+    /// hotkey events would never be received and unregistering would fail.
+    ///
+    fn check_thread(&self) -> Result<(), HkError> {
+        match (self.backend.owner_thread_id(), backend::current_thread_id()) {
+            (Some(owner), Some(current)) if owner != current => Err(HkError::WrongThread(owner)),
+            _ => Ok(()),
+        }
+    }
+
+    /// Check if the hotkey is currently registered with windows, meaning that it is enabled and
+    /// the HotkeyManager is not paused.
+    ///
+    fn is_active(&self, handler: &HotkeyCallback<T>) -> bool {
+        handler.enabled && !self.paused
+    }
+
+    /// Find the id of the hotkey that was registered for the given key combination.
+    ///
+    fn find_combo(&self, key: VKey, key_modifiers: &[ModKey]) -> Option<HotkeyId> {
+        self.handlers
+            .iter()
+            .find(|(_, handler)| handler.matches_combo(key, key_modifiers))
+            .map(|(id, _)| *id)
+    }
+
+    /// Take a single hotkey event from the message queue without blocking and process it. Returns
+    /// `None` if there are no hotkey events in the queue.
+    ///
+    /// Outside of the owner thread, `None` is returned without reading the message queue, which
+    /// belongs to another thread and would not contain the hotkey events of this HotkeyManager.
+    ///
+    fn peek_hotkey(&self) -> Option<Dispatch<T>> {
+        if let Err(err) = self.check_thread() {
+            log_debug!("not reading the message queue: {}", err);
+            return None;
+        }
+
+        // Only take hotkey events from the queue
+        let msg = self.backend.peek_message(true)?;
+
+        Some(self.dispatch(msg))
+    }
+
+    /// Process a message from the message queue. If the message is a hotkey event, the matching
+    /// callback is executed when all of its extra keys are pressed.
+    ///
+    fn dispatch(&self, msg: BackendMessage) -> Dispatch<T> {
+        match msg {
+            BackendMessage::Hotkey(hk_id, trigger) => {
+                log_trace!("received hotkey event for {:?}", hk_id);
+
+                // The span covers the whole handling of the event, the combination and the
+                // reason why the event was ignored are recorded once they are known
+                #[cfg(feature = "tracing")]
+                let span = tracing::debug_span!(
+                    "hotkey_event",
+                    hotkey.id = hk_id.as_i32(),
+                    hotkey.combo = tracing::field::Empty,
+                    hotkey.ignored = tracing::field::Empty,
+                );
+                #[cfg(feature = "tracing")]
+                let _entered = span.enter();
+
+                // Get the callback for the received ID. Events that were already queued before
+                // the hotkey was disabled or paused are skipped
+                let Some(handler) = self.handlers.get(&hk_id).filter(|h| self.is_active(h)) else {
+                    if !self.handlers.contains_key(&hk_id) {
+                        return self.dispatch_fallback(hk_id, trigger);
+                    }
+                    log_debug!(
+                        "ignored hotkey event for {:?}: the hotkey is not active",
+                        hk_id
+                    );
+                    return Dispatch::Ignored;
+                };
+                #[cfg(feature = "tracing")]
+                span.record(
+                    "hotkey.combo",
+                    tracing::field::display(handler.combination()),
+                );
+
+                // A callback that handles hotkey events itself, for example through a nested event
+                // loop, can receive its own hotkey again while it is still running. The callback
+                // stays borrowed until it returns or panics, so the reentrant event is skipped
+                let Ok(mut callback) = handler.callback.try_borrow_mut() else {
+                    #[cfg(feature = "tracing")]
+                    span.record("hotkey.ignored", "the callback is already running");
+                    log_debug!(
+                        "ignored hotkey event for {:?} ({}): the callback is already running",
+                        hk_id,
+                        handler.combination()
+                    );
+                    return Dispatch::Ignored;
+                };
+
+                // Check if all extra keys are pressed and the condition is met
+                let key_state = |vk| self.backend.key_state(vk);
+                if let Err(reason) = handler.accept_event(trigger, key_state, Instant::now()) {
+                    #[cfg(feature = "tracing")]
+                    span.record("hotkey.ignored", reason);
+                    log_debug!(
+                        "ignored hotkey event for {:?} ({}): {}",
+                        hk_id,
+                        handler.combination(),
+                        reason
+                    );
+                    return Dispatch::Ignored;
+                }
+
+                log_debug!(
+                    "executing the callback of {:?} ({})",
+                    hk_id,
+                    handler.combination()
+                );
+
+                // Catch panics so a single faulty callback doesn't tear down the event loop. The
+                // callback is not used again by the unwinding code, so the unwind safety can be
+                // asserted
+                let context = HotkeyContext {
+                    id: hk_id,
+                    key: handler.key,
+                    modifiers: trigger.map_or_else(
+                        || handler.key_modifiers.clone(),
+                        |trigger| trigger.modifiers(),
+                    ),
+                };
+                let callback = AssertUnwindSafe(|| {
+                    #[cfg(feature = "tracing")]
+                    let _entered = tracing::debug_span!("hotkey_callback").entered();
+
+                    callback(&context)
+                });
+                match panic::catch_unwind(callback) {
+                    Ok(result) => Dispatch::Callback(hotkey_event(hk_id, result, trigger)),
+                    Err(_) => {
+                        log_debug!("the callback of {:?} panicked", hk_id);
+                        Dispatch::Panicked(hk_id)
+                    }
+                }
+            }
+            BackendMessage::Interrupt | BackendMessage::Quit => Dispatch::Interrupt,
+            BackendMessage::Other => Dispatch::Ignored,
+        }
+    }
+
+    /// Execute the fallback callback for a hotkey event whose id doesn't belong to any hotkey.
+    /// The event is ignored if there is no fallback or the fallback is already running.
+    ///
+    fn dispatch_fallback(&self, hk_id: HotkeyId, trigger: Option<HotkeyTrigger>) -> Dispatch<T> {
+        let Ok(mut fallback) = self.fallback.try_borrow_mut() else {
+            return Dispatch::Ignored;
+        };
+        let Some(fallback) = fallback.as_mut() else {
+            log_debug!("ignored hotkey event for {:?}: unknown hotkey", hk_id);
+            return Dispatch::Ignored;
+        };
+
+        log_debug!("executing the fallback callback for {:?}", hk_id);
+        match panic::catch_unwind(AssertUnwindSafe(|| fallback(hk_id))) {
+            Ok(result) => Dispatch::Callback(hotkey_event(hk_id, result, trigger)),
+            Err(_) => Dispatch::Panicked(hk_id),
+        }
+    }
+}
+
+/// A callback for hotkey events without a registered hotkey
+type FallbackCallback<T> = Box<dyn FnMut(HotkeyId) -> T + 'static>;
+
+/// Create the `HotkeyEvent` for an executed callback
+///
+fn hotkey_event<T>(id: HotkeyId, result: T, trigger: Option<HotkeyTrigger>) -> HotkeyEvent<T> {
+    HotkeyEvent {
+        id,
+        result,
+        time: trigger.and_then(|trigger| trigger.time()),
+        cursor_pos: trigger.and_then(|trigger| trigger.cursor_pos()),
     }
 }
 
@@ -172,44 +867,807 @@ impl<T> Drop for HotkeyManager<T> {
     }
 }
 
-/// Wrapper around a HWND windows pointer that destroys the window on drop
+/// Outcome of processing a single message from the message queue
 ///
-struct HwndDropper(HWND);
+enum Dispatch<T> {
+    /// A hotkey was triggered and the callback was executed
+    Callback(HotkeyEvent<T>),
+    /// A hotkey was triggered and the callback panicked
+    Panicked(HotkeyId),
+    /// The event loop was interrupted through an `InterruptHandle` or stopped by `WM_QUIT`
+    Interrupt,
+    /// The message didn't lead to a callback being executed
+    Ignored,
+}
 
-impl Drop for HwndDropper {
-    fn drop(&mut self) {
-        if !self.0.is_null() {
-            let _ = unsafe { DestroyWindow(self.0) };
+/// Hands out the ids for new hotkeys. Ids of unregistered hotkeys are reused before new ids are
+/// handed out. After `i32::MAX` was handed out, `HkError::IdExhausted` is returned.
+///
+enum IdAllocator {
+    /// Use the process-global id pool, so the ids never collide with other HotkeyManagers that
+    /// use the pool, even on other threads
+    Shared,
+    /// Count the ids in the HotkeyManager, starting from an id offset
+    Local {
+        /// The next id to hand out. Counted as `i64`, so `i32::MAX` can be handed out as the last
+        /// id
+        next: i64,
+        /// The id offset that the HotkeyManager was created with
+        initial: i32,
+        /// Ids of unregistered hotkeys that are handed out again before advancing `next`
+        free: Vec<i32>,
+    },
+}
+
+impl IdAllocator {
+    fn local(id_offset: i32) -> IdAllocator {
+        IdAllocator::Local {
+            next: id_offset.into(),
+            initial: id_offset,
+            free: Vec::new(),
+        }
+    }
+
+    fn allocate(&mut self) -> Result<HotkeyId, HkError> {
+        match self {
+            IdAllocator::Shared => {
+                if let Some(id) = lock_free_shared_ids().pop() {
+                    return Ok(HotkeyId(id));
+                }
+
+                // The shared ids start at 0, so the scratch id of `can_register` is never reached
+                NEXT_SHARED_ID
+                    .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |id| {
+                        (id <= i32::MAX.into()).then_some(id + 1)
+                    })
+                    .map(|id| HotkeyId(id as i32))
+                    .map_err(|_| HkError::IdExhausted)
+            }
+            IdAllocator::Local { next, free, .. } => {
+                if let Some(id) = free.pop() {
+                    return Ok(HotkeyId(id));
+                }
+
+                // The scratch id of `can_register` can only be reached by starting at it
+                if *next == SCRATCH_ID.0.into() {
+                    *next += 1;
+                }
+
+                let id = i32::try_from(*next).map_err(|_| HkError::IdExhausted)?;
+                *next += 1;
+
+                Ok(HotkeyId(id))
+            }
+        }
+    }
+
+    /// Return an id that is no longer used, so it can be handed out again.
+    ///
+    fn release(&mut self, id: HotkeyId) {
+        match self {
+            IdAllocator::Shared => lock_free_shared_ids().push(id.0),
+            IdAllocator::Local { free, .. } => free.push(id.0),
+        }
+    }
+
+    /// Reset the id counter to the initial id offset. The shared id pool is not reset, since
+    /// other HotkeyManagers might still use its ids.
+    ///
+    fn reset(&mut self) {
+        if let IdAllocator::Local {
+            next,
+            initial,
+            free,
+        } = self
+        {
+            *next = (*initial).into();
+            free.clear();
         }
     }
 }
 
-/// Try to create a hidden "message-only" window
+/// Lock the free ids of the process-global id pool. The ids stay valid if another thread
+/// panicked while holding the lock.
 ///
-fn create_hidden_window() -> Result<HwndDropper, ()> {
-    let hwnd = unsafe {
-        // Get the current module handle
-        let hinstance = GetModuleHandleA(std::ptr::null_mut());
-        CreateWindowExA(
-            WS_EX_NOACTIVATE,
-            // The "Static" class is not intended for windows, but this shouldn't matter since the
-            // window is hidden anyways
-            b"Static\0".as_ptr() as *const i8,
-            b"\0".as_ptr() as *const i8,
-            WS_DISABLED,
-            0,
-            0,
-            0,
-            0,
-            HWND_MESSAGE,
-            std::ptr::null_mut(),
-            hinstance,
-            std::ptr::null_mut(),
-        )
+fn lock_free_shared_ids() -> MutexGuard<'static, Vec<i32>> {
+    FREE_SHARED_IDS
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{atomic::AtomicBool, Arc};
+
+    use super::*;
+    use crate::{
+        backend::mock::{Call, MockBackend},
+        config::{BindingConfig, HotkeyConfig},
+        Binding,
     };
-    if hwnd.is_null() {
-        Err(())
-    } else {
-        Ok(HwndDropper(hwnd))
+
+    /// Create a HotkeyManager with a `MockBackend`, the backend is kept to inject the events
+    ///
+    fn manager() -> (HotkeyManager<u32>, MockBackend) {
+        let backend = MockBackend::new();
+        (HotkeyManager::with_backend(backend.clone()), backend)
+    }
+
+    #[test]
+    fn register_and_unregister_call_the_backend() {
+        let (mut hkm, backend) = manager();
+
+        let id = hkm.register(VKey::A, &[ModKey::Ctrl], || 1).unwrap();
+        hkm.unregister(id).unwrap();
+
+        assert_eq!(
+            backend.calls(),
+            vec![
+                Call::Register(id, VKey::A, vec![ModKey::Ctrl]),
+                Call::Unregister(id)
+            ]
+        );
+        assert!(backend.registered_ids().is_empty());
+        assert!(hkm.is_empty());
+    }
+
+    #[test]
+    fn failed_registration_is_not_stored() {
+        let (mut hkm, backend) = manager();
+        backend.fail_register(VKey::A, true);
+
+        let err = hkm.register(VKey::A, &[ModKey::Ctrl], || 1).unwrap_err();
+
+        assert!(matches!(err, HkError::RegistrationFailed(_)));
+        assert!(hkm.is_empty());
+    }
+
+    #[test]
+    fn ids_up_to_i32_max_are_handed_out() {
+        let mut ids = IdAllocator::local(i32::MAX - 1);
+
+        assert_eq!(ids.allocate(), Ok(HotkeyId(i32::MAX - 1)));
+        assert_eq!(ids.allocate(), Ok(HotkeyId(i32::MAX)));
+        assert_eq!(ids.allocate(), Err(HkError::IdExhausted));
+        assert_eq!(ids.allocate(), Err(HkError::IdExhausted));
+
+        // Released ids can still be handed out after the counter is exhausted
+        ids.release(HotkeyId(i32::MAX));
+        assert_eq!(ids.allocate(), Ok(HotkeyId(i32::MAX)));
+    }
+
+    #[test]
+    fn manager_near_i32_max_reports_exhausted_ids() {
+        let backend = MockBackend::new();
+        let mut hkm: HotkeyManager<u32> =
+            HotkeyManager::from_backend(Box::new(backend), IdAllocator::local(i32::MAX));
+
+        let id = hkm.register(VKey::A, &[ModKey::Ctrl], || 1).unwrap();
+        assert_eq!(id, HotkeyId(i32::MAX));
+        assert_eq!(
+            hkm.register(VKey::B, &[ModKey::Ctrl], || 2),
+            Err(HkError::IdExhausted)
+        );
+        assert_eq!(hkm.len(), 1);
+    }
+
+    #[test]
+    fn failed_unregistration_keeps_the_hotkey() {
+        let (mut hkm, backend) = manager();
+        let id = hkm.register(VKey::A, &[ModKey::Ctrl], || 1).unwrap();
+        backend.fail_unregister(id, true);
+
+        assert!(matches!(
+            hkm.unregister(id),
+            Err(HkError::UnregistrationFailed(_))
+        ));
+        assert_eq!(hkm.registered_ids(), vec![id]);
+
+        backend.push_hotkey(id);
+        assert_eq!(hkm.try_handle_hotkey(), Some(1));
+    }
+
+    #[test]
+    fn modifiers_are_accepted_in_any_form() {
+        let (mut hkm, backend) = manager();
+        let modifiers = vec![ModKey::Ctrl, ModKey::Shift];
+
+        let a = hkm.register(VKey::A, &modifiers, || 1).unwrap();
+        let b = hkm.register(VKey::B, modifiers.clone(), || 2).unwrap();
+        let c = hkm
+            .register(VKey::C, ModKey::Ctrl | ModKey::Shift, || 3)
+            .unwrap();
+        let d = hkm
+            .register_extrakeys(VKey::D, &modifiers, &[VKey::LShift], || 4)
+            .unwrap();
+        hkm.rebind(d, VKey::E, &modifiers).unwrap();
+
+        for (id, key) in [(a, VKey::A), (b, VKey::B), (c, VKey::C), (d, VKey::E)] {
+            assert!(backend
+                .calls()
+                .contains(&Call::Register(id, key, modifiers.clone())));
+        }
+    }
+
+    #[test]
+    fn extra_keys_must_be_pressed() {
+        let (mut hkm, backend) = manager();
+        let id = hkm
+            .register_extrakeys(VKey::A, &[ModKey::Ctrl], &[VKey::LShift], || 1)
+            .unwrap();
+
+        backend.push_hotkey(id);
+        assert_eq!(hkm.drain_hotkeys(), Vec::<u32>::new());
+
+        backend.set_key_state(VKey::LShift, true);
+        backend.push_hotkey(id);
+        assert_eq!(hkm.drain_hotkeys(), vec![1]);
+        assert_eq!(hkm.fire_count(id), Some(1));
+    }
+
+    #[test]
+    fn condition_gates_the_callback() {
+        let (mut hkm, backend) = manager();
+        let allowed = Arc::new(AtomicBool::new(false));
+        let condition = allowed.clone();
+        let id = hkm
+            .register_conditional(
+                VKey::A,
+                &[ModKey::Ctrl],
+                move || condition.load(Ordering::SeqCst),
+                || 1,
+            )
+            .unwrap();
+
+        backend.push_hotkey(id);
+        assert_eq!(hkm.try_handle_hotkey(), None);
+
+        allowed.store(true, Ordering::SeqCst);
+        backend.push_hotkey(id);
+        assert_eq!(hkm.try_handle_hotkey(), Some(1));
+    }
+
+    #[test]
+    fn rebind_changes_the_combination() {
+        let (mut hkm, backend) = manager();
+        let id = hkm.register(VKey::A, &[ModKey::Ctrl], || 1).unwrap();
+
+        hkm.rebind(id, VKey::B, ModKey::Alt).unwrap();
+
+        assert_eq!(
+            backend.calls(),
+            vec![
+                Call::Register(id, VKey::A, vec![ModKey::Ctrl]),
+                Call::Unregister(id),
+                Call::Register(id, VKey::B, vec![ModKey::Alt]),
+            ]
+        );
+        assert_eq!(backend.registered_ids(), vec![id]);
+        let info = hkm
+            .registered()
+            .into_iter()
+            .find(|info| info.id == id)
+            .unwrap();
+        assert_eq!(info.key, VKey::B);
+    }
+
+    #[test]
+    fn failed_rebind_rolls_back_to_the_previous_combination() {
+        let (mut hkm, backend) = manager();
+        let id = hkm.register(VKey::A, &[ModKey::Ctrl], || 1).unwrap();
+        backend.fail_register(VKey::B, true);
+
+        assert!(matches!(
+            hkm.rebind(id, VKey::B, ModKey::Alt),
+            Err(HkError::RegistrationFailed(_))
+        ));
+
+        assert_eq!(
+            backend.calls().last(),
+            Some(&Call::Register(id, VKey::A, vec![ModKey::Ctrl]))
+        );
+        assert_eq!(backend.registered_ids(), vec![id]);
+        backend.push_hotkey(id);
+        assert_eq!(hkm.try_handle_hotkey(), Some(1));
+    }
+
+    #[test]
+    fn failed_rollback_disables_the_hotkey() {
+        let (mut hkm, backend) = manager();
+        let id = hkm.register(VKey::A, &[ModKey::Ctrl], || 1).unwrap();
+        backend.fail_register(VKey::A, true);
+        backend.fail_register(VKey::B, true);
+
+        match hkm.rebind(id, VKey::B, ModKey::Alt) {
+            Err(HkError::RollbackFailed { id: failed, error }) => {
+                assert_eq!(failed, id);
+                assert!(matches!(*error, HkError::RegistrationFailed(_)));
+            }
+            other => panic!("unexpected result {:?}", other),
+        }
+        assert!(backend.registered_ids().is_empty());
+        assert_eq!(hkm.is_enabled(id), Some(false));
+
+        // Enabling the hotkey registers the previous combination again
+        backend.fail_register(VKey::A, false);
+        hkm.enable(id).unwrap();
+        assert_eq!(
+            backend.calls().last(),
+            Some(&Call::Register(id, VKey::A, vec![ModKey::Ctrl]))
+        );
+    }
+
+    #[test]
+    fn rebind_rejects_a_combination_of_another_hotkey() {
+        let (mut hkm, backend) = manager();
+        let first = hkm.register(VKey::A, &[ModKey::Ctrl], || 1).unwrap();
+        let second = hkm.register(VKey::B, &[ModKey::Ctrl], || 2).unwrap();
+        let calls = backend.calls().len();
+
+        assert_eq!(
+            hkm.rebind(second, VKey::A, ModKey::Ctrl),
+            Err(HkError::AlreadyRegistered {
+                key: VKey::A,
+                modifiers: vec![ModKey::Ctrl],
+                id: Some(first),
+            })
+        );
+        assert_eq!(backend.calls().len(), calls);
+
+        // Rebinding a hotkey to its own combination is allowed
+        hkm.rebind(first, VKey::A, ModKey::Ctrl).unwrap();
+    }
+
+    #[test]
+    fn ids_of_unregistered_hotkeys_are_reused() {
+        let (mut hkm, backend) = manager();
+        let first = hkm.register(VKey::A, &[ModKey::Ctrl], || 1).unwrap();
+        let second = hkm.register(VKey::B, &[ModKey::Ctrl], || 2).unwrap();
+        assert_ne!(first, second);
+
+        hkm.unregister(first).unwrap();
+        let third = hkm.register(VKey::C, &[ModKey::Ctrl], || 3).unwrap();
+        assert_eq!(third, first);
+
+        // A failed registration doesn't use up the id either
+        hkm.unregister(second).unwrap();
+        backend.fail_register(VKey::E, true);
+        assert!(hkm.register(VKey::E, &[ModKey::Ctrl], || 4).is_err());
+        assert_eq!(hkm.register(VKey::D, &[ModKey::Ctrl], || 4), Ok(second));
+    }
+
+    #[test]
+    fn disable_and_enable_round_trip() {
+        let (mut hkm, backend) = manager();
+        let id = hkm.register(VKey::A, &[ModKey::Ctrl], || 1).unwrap();
+
+        hkm.disable(id).unwrap();
+        assert_eq!(hkm.is_enabled(id), Some(false));
+        assert!(backend.registered_ids().is_empty());
+        assert_eq!(hkm.trigger(id), Err(HkError::Inactive(id)));
+
+        // Disabling twice doesn't unregister the hotkey again
+        hkm.disable(id).unwrap();
+        assert_eq!(
+            backend.calls(),
+            vec![
+                Call::Register(id, VKey::A, vec![ModKey::Ctrl]),
+                Call::Unregister(id)
+            ]
+        );
+
+        hkm.enable(id).unwrap();
+        assert_eq!(hkm.is_enabled(id), Some(true));
+        assert_eq!(backend.registered_ids(), vec![id]);
+        assert_eq!(
+            backend.calls().last(),
+            Some(&Call::Register(id, VKey::A, vec![ModKey::Ctrl]))
+        );
+
+        backend.push_hotkey(id);
+        assert_eq!(hkm.try_handle_hotkey(), Some(1));
+    }
+
+    #[test]
+    fn unregistering_a_disabled_hotkey_skips_the_backend() {
+        let (mut hkm, backend) = manager();
+        let id = hkm.register(VKey::A, &[ModKey::Ctrl], || 1).unwrap();
+        hkm.disable(id).unwrap();
+        let calls = backend.calls().len();
+
+        hkm.unregister(id).unwrap();
+        assert_eq!(backend.calls().len(), calls);
+        assert!(hkm.is_empty());
+    }
+
+    #[test]
+    fn pause_and_resume_keep_the_ids() {
+        let (mut hkm, backend) = manager();
+        let first = hkm.register(VKey::A, &[ModKey::Ctrl], || 1).unwrap();
+        let second = hkm.register(VKey::B, &[ModKey::Ctrl], || 2).unwrap();
+        let disabled = hkm.register(VKey::C, &[ModKey::Ctrl], || 3).unwrap();
+        hkm.disable(disabled).unwrap();
+
+        hkm.pause_all().unwrap();
+        assert!(backend.registered_ids().is_empty());
+        assert_eq!(hkm.registered_ids(), vec![first, second, disabled]);
+
+        // Paused hotkeys are not handled, even if an event was still queued
+        backend.push_hotkey(first);
+        assert_eq!(hkm.try_handle_hotkey(), None);
+
+        hkm.resume_all().unwrap();
+        assert_eq!(hkm.registered_ids(), vec![first, second, disabled]);
+        assert_eq!(backend.registered_ids(), vec![first, second]);
+        assert_eq!(hkm.is_enabled(disabled), Some(false));
+
+        backend.push_hotkey(second);
+        assert_eq!(hkm.try_handle_hotkey(), Some(2));
+    }
+
+    #[test]
+    fn failed_resume_disables_the_hotkey() {
+        let (mut hkm, backend) = manager();
+        let first = hkm.register(VKey::A, &[ModKey::Ctrl], || 1).unwrap();
+        let second = hkm.register(VKey::B, &[ModKey::Ctrl], || 2).unwrap();
+        hkm.pause_all().unwrap();
+        backend.fail_register(VKey::A, true);
+
+        match hkm.resume_all() {
+            Err(HkError::Multiple(errors)) => {
+                assert_eq!(errors.len(), 1);
+                assert_eq!(errors[0].0, first);
+            }
+            other => panic!("unexpected result {:?}", other),
+        }
+        assert_eq!(hkm.is_enabled(first), Some(false));
+        assert_eq!(backend.registered_ids(), vec![second]);
+    }
+
+    #[test]
+    fn register_many_rolls_back_after_a_failure() {
+        let (mut hkm, backend) = manager();
+        let existing = hkm.register(VKey::X, &[ModKey::Ctrl], || 0).unwrap();
+        backend.fail_register(VKey::C, true);
+
+        let bindings = vec![
+            Binding::new(VKey::A, &[ModKey::Ctrl], || 1),
+            Binding::new(VKey::B, &[ModKey::Ctrl], || 2),
+            Binding::new(VKey::C, &[ModKey::Ctrl], || 3),
+            Binding::new(VKey::D, &[ModKey::Ctrl], || 4),
+        ];
+        let err = hkm.register_many(bindings).unwrap_err();
+        assert!(matches!(err, HkError::RegistrationFailed(_)));
+
+        // The hotkeys of the batch are unregistered in reverse order, the binding after the
+        // failed one is never attempted
+        let calls = backend.calls();
+        let ids: Vec<_> = calls
+            .iter()
+            .filter_map(|call| match call {
+                Call::Register(id, VKey::A | VKey::B, _) => Some(*id),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            calls[calls.len() - 2..],
+            [Call::Unregister(ids[1]), Call::Unregister(ids[0])]
+        );
+        assert!(!calls
+            .iter()
+            .any(|call| matches!(call, Call::Register(_, VKey::D, _))));
+
+        assert_eq!(hkm.registered_ids(), vec![existing]);
+        assert_eq!(backend.registered_ids(), vec![existing]);
+    }
+
+    #[test]
+    fn register_range_registers_the_named_keys() {
+        let (mut hkm, backend) = manager();
+
+        let ids = hkm
+            .register_range(VKey::Vk1, 3, &[ModKey::Win], |offset| offset as u32 + 1)
+            .unwrap();
+
+        let keys: Vec<_> = hkm.registered().into_iter().map(|info| info.key).collect();
+        assert!(matches!(keys[..], [VKey::Vk1, VKey::Vk2, VKey::Vk3]));
+
+        backend.push_hotkey(ids[2]);
+        assert_eq!(hkm.try_handle_hotkey(), Some(3));
+    }
+
+    #[test]
+    fn register_range_rejects_invalid_keycodes() {
+        let (mut hkm, backend) = manager();
+
+        assert_eq!(
+            hkm.register_range(VKey::CustomKeyCode(0xFD), 3, &[ModKey::Win], |_| 0),
+            Err(HkError::InvalidKey("0xFF".to_string()))
+        );
+        assert!(backend.calls().is_empty());
+    }
+
+    #[test]
+    fn register_many_returns_the_ids_in_order() {
+        let (mut hkm, _) = manager();
+
+        let ids = hkm
+            .register_many(vec![
+                Binding::new(VKey::A, &[ModKey::Ctrl], || 1),
+                Binding::new(VKey::B, &[ModKey::Ctrl], || 2),
+            ])
+            .unwrap();
+
+        assert_eq!(hkm.registered_ids(), ids);
+    }
+
+    #[test]
+    fn unregister_all_continues_after_a_failure() {
+        let (mut hkm, backend) = manager();
+        let first = hkm.register(VKey::A, &[ModKey::Ctrl], || 1).unwrap();
+        let failing = hkm.register(VKey::B, &[ModKey::Ctrl], || 2).unwrap();
+        let last = hkm.register(VKey::C, &[ModKey::Ctrl], || 3).unwrap();
+        backend.fail_unregister(failing, true);
+
+        match hkm.unregister_all() {
+            Err(HkError::Multiple(errors)) => {
+                assert_eq!(errors.len(), 1);
+                assert_eq!(errors[0].0, failing);
+                assert!(matches!(errors[0].1, HkError::UnregistrationFailed(_)));
+            }
+            other => panic!("unexpected result {:?}", other),
+        }
+
+        // Every hotkey was attempted, only the failed one is kept
+        for id in [first, failing, last] {
+            assert!(backend.calls().contains(&Call::Unregister(id)));
+        }
+        assert_eq!(hkm.registered_ids(), vec![failing]);
+        assert_eq!(backend.registered_ids(), vec![failing]);
+
+        backend.fail_unregister(failing, false);
+        hkm.unregister_all().unwrap();
+        assert!(hkm.is_empty());
+    }
+
+    #[test]
+    fn mismatched_wm_hotkey_lparam_is_ignored() {
+        let (mut hkm, backend) = manager();
+        let id = hkm.register(VKey::A, &[ModKey::Ctrl], || 1).unwrap();
+        let ctrl = ModKey::Ctrl.to_mod_code() as isize;
+
+        // The high word of the lParam is the key, the low word the modifiers
+        backend.push_hotkey_lparam(id, ((VKey::B.to_vk_code() as isize) << 16) | ctrl);
+        assert_eq!(hkm.try_handle_hotkey(), None);
+        assert_eq!(hkm.fire_count(id), Some(0));
+
+        backend.push_hotkey_lparam(id, ((VKey::A.to_vk_code() as isize) << 16) | ctrl);
+        assert_eq!(hkm.try_handle_hotkey(), Some(1));
+    }
+
+    #[test]
+    fn apply_config_disables_bindings() {
+        let (mut hkm, backend) = manager();
+        let config = HotkeyConfig {
+            bindings: vec![
+                BindingConfig {
+                    combo: "Ctrl+A".to_string(),
+                    action: "first".to_string(),
+                    ..Default::default()
+                },
+                BindingConfig {
+                    combo: "Ctrl+B".to_string(),
+                    action: "second".to_string(),
+                    enabled: false,
+                    ..Default::default()
+                },
+            ],
+        };
+
+        let ids: Vec<_> = hkm
+            .apply_config(&config, |_| Some(Box::new(|| 1)))
+            .into_iter()
+            .collect::<Result<_, _>>()
+            .unwrap();
+
+        assert_eq!(hkm.is_enabled(ids[0]), Some(true));
+        assert_eq!(hkm.is_enabled(ids[1]), Some(false));
+        assert_eq!(backend.registered_ids(), vec![ids[0]]);
+    }
+
+    #[test]
+    fn dump_config_round_trips_through_apply_config() {
+        let (mut hkm, _) = manager();
+        let options = RegisterOptions {
+            label: Some("open".to_string()),
+            ..Default::default()
+        };
+        hkm.register_extrakeys_opts(VKey::A, &[ModKey::Ctrl], &[VKey::LShift], options, || 1)
+            .unwrap();
+        let options = RegisterOptions {
+            label: Some("close".to_string()),
+            ..Default::default()
+        };
+        let id = hkm
+            .register_extrakeys_opts(VKey::B, &[ModKey::Alt], &[], options, || 2)
+            .unwrap();
+        hkm.disable(id).unwrap();
+        let dumped = hkm.dump_config();
+
+        let (mut other, _) = manager();
+        let config: HotkeyConfig = dumped.clone().into_iter().collect();
+        for res in other.apply_config(&config, |_| Some(Box::new(|| 0))) {
+            res.unwrap();
+        }
+
+        assert_eq!(other.dump_config(), dumped);
+    }
+
+    #[test]
+    fn register_replacing_replaces_the_hotkey() {
+        let (mut hkm, backend) = manager();
+        let old = hkm.register(VKey::A, &[ModKey::Ctrl], || 1).unwrap();
+
+        let new = hkm
+            .register_replacing(VKey::A, &[ModKey::Ctrl], || 2)
+            .unwrap();
+
+        assert_ne!(new, old);
+        assert!(backend.calls().contains(&Call::Unregister(old)));
+        assert_eq!(hkm.registered_ids(), vec![new]);
+        assert_eq!(backend.registered_ids(), vec![new]);
+        backend.push_hotkey(new);
+        assert_eq!(hkm.try_handle_hotkey(), Some(2));
+
+        // Without a registered hotkey, this is the same as `register`
+        hkm.register_replacing(VKey::B, &[ModKey::Ctrl], || 3)
+            .unwrap();
+        assert_eq!(hkm.len(), 2);
+    }
+
+    #[test]
+    fn failed_register_replacing_restores_the_replaced_hotkey() {
+        let (mut hkm, backend) = manager();
+        let old = hkm.register(VKey::A, &[ModKey::Ctrl], || 1).unwrap();
+        // The new hotkey gets the next id
+        let new = HotkeyId(old.0 + 1);
+        backend.fail_register_id(new, true);
+
+        assert!(matches!(
+            hkm.register_replacing(VKey::A, &[ModKey::Ctrl], || 2),
+            Err(HkError::RegistrationFailed(_))
+        ));
+
+        assert_eq!(
+            backend.calls()[1..],
+            [
+                Call::Unregister(old),
+                Call::Register(new, VKey::A, vec![ModKey::Ctrl]),
+                Call::Register(old, VKey::A, vec![ModKey::Ctrl]),
+            ]
+        );
+        assert_eq!(hkm.registered_ids(), vec![old]);
+        assert_eq!(backend.registered_ids(), vec![old]);
+        backend.push_hotkey(old);
+        assert_eq!(hkm.try_handle_hotkey(), Some(1));
+    }
+
+    #[test]
+    fn register_replacing_without_ids_keeps_the_replaced_hotkey() {
+        let backend = MockBackend::new();
+        let mut hkm: HotkeyManager<u32> =
+            HotkeyManager::from_backend(Box::new(backend.clone()), IdAllocator::local(i32::MAX));
+        let old = hkm.register(VKey::A, &[ModKey::Ctrl], || 1).unwrap();
+
+        assert_eq!(
+            hkm.register_replacing(VKey::A, &[ModKey::Ctrl], || 2),
+            Err(HkError::IdExhausted)
+        );
+
+        assert_eq!(backend.registered_ids(), vec![old]);
+        backend.push_hotkey(old);
+        assert_eq!(hkm.try_handle_hotkey(), Some(1));
+    }
+
+    #[test]
+    fn failed_register_replacing_rollback_disables_the_replaced_hotkey() {
+        let (mut hkm, backend) = manager();
+        let old = hkm.register(VKey::A, &[ModKey::Ctrl], || 1).unwrap();
+        backend.fail_register(VKey::A, true);
+
+        match hkm.register_replacing(VKey::A, &[ModKey::Ctrl], || 2) {
+            Err(HkError::RollbackFailed { id, error }) => {
+                assert_eq!(id, old);
+                assert!(matches!(*error, HkError::RegistrationFailed(_)));
+            }
+            other => panic!("unexpected result {:?}", other),
+        }
+
+        assert_eq!(hkm.registered_ids(), vec![old]);
+        assert_eq!(hkm.is_enabled(old), Some(false));
+        assert!(backend.registered_ids().is_empty());
+
+        backend.fail_register(VKey::A, false);
+        hkm.enable(old).unwrap();
+        assert_eq!(backend.registered_ids(), vec![old]);
+    }
+
+    #[test]
+    fn every_backend_call_checks_the_thread() {
+        let (mut hkm, backend) = manager();
+        let id = hkm.register(VKey::A, &[ModKey::Ctrl], || 1).unwrap();
+        let disabled = hkm.register(VKey::B, &[ModKey::Ctrl], || 2).unwrap();
+        hkm.disable(disabled).unwrap();
+        backend.push_hotkey(id);
+
+        let other = std::thread::spawn(backend::current_thread_id)
+            .join()
+            .unwrap();
+        assert_ne!(other, backend::current_thread_id());
+        backend.set_owner_thread_id(other);
+        let wrong_thread = Err(HkError::WrongThread(other.unwrap()));
+        let calls = backend.calls().len();
+
+        assert_eq!(
+            hkm.register(VKey::C, &[ModKey::Ctrl], || 3),
+            wrong_thread.clone().map(|()| id)
+        );
+        assert_eq!(hkm.rebind(id, VKey::C, ModKey::Ctrl), wrong_thread);
+        assert_eq!(hkm.disable(id), wrong_thread);
+        assert_eq!(hkm.enable(disabled), wrong_thread);
+        assert_eq!(hkm.trigger(id), wrong_thread);
+        assert_eq!(hkm.pause_all(), wrong_thread);
+        assert_eq!(hkm.resume_all(), wrong_thread);
+        assert_eq!(hkm.can_register(VKey::C, &[ModKey::Ctrl]), wrong_thread);
+        assert_eq!(hkm.unregister(id), wrong_thread);
+        assert_eq!(hkm.unregister_all(), wrong_thread);
+        assert_eq!(hkm.handle_hotkey(), wrong_thread.clone().map(|()| None));
+
+        // The queue is not read outside of the owner thread
+        assert_eq!(hkm.try_handle_hotkey(), None);
+        assert_eq!(hkm.drain_hotkeys(), Vec::<u32>::new());
+
+        assert_eq!(backend.calls().len(), calls);
+        assert_eq!(hkm.is_enabled(id), Some(true));
+        assert_eq!(hkm.is_enabled(disabled), Some(false));
+
+        backend.set_owner_thread_id(backend::current_thread_id());
+        assert_eq!(hkm.try_handle_hotkey(), Some(1));
+    }
+
+    #[test]
+    fn find_by_label_returns_the_lowest_id() {
+        let (mut hkm, _) = manager();
+        let labeled = |label: &str| RegisterOptions {
+            label: Some(label.to_string()),
+            ..Default::default()
+        };
+        let unlabeled = hkm.register(VKey::A, ModKey::Ctrl, || 0).unwrap();
+        let first = hkm
+            .register_extrakeys_opts(VKey::B, &[ModKey::Ctrl], &[], labeled("open"), || 1)
+            .unwrap();
+        let second = hkm
+            .register_extrakeys_opts(VKey::C, &[ModKey::Ctrl], &[], labeled("open"), || 2)
+            .unwrap();
+
+        assert_eq!(hkm.label(unlabeled), None);
+        assert_eq!(hkm.label(second).as_deref(), Some("open"));
+        assert_eq!(hkm.label(HotkeyId(100)), None);
+        assert_eq!(hkm.find_by_label("open"), Some(first));
+        assert_eq!(hkm.find_by_label("close"), None);
+
+        hkm.unregister(first).unwrap();
+        assert_eq!(hkm.find_by_label("open"), Some(second));
+    }
+
+    #[test]
+    fn is_enabled_is_none_for_unknown_hotkeys() {
+        let (mut hkm, _) = manager();
+        let id = hkm.register(VKey::A, ModKey::Ctrl, || 0).unwrap();
+        assert_eq!(hkm.is_enabled(id), Some(true));
+        assert_eq!(hkm.is_enabled(HotkeyId(100)), None);
+
+        hkm.unregister(id).unwrap();
+        assert_eq!(hkm.is_enabled(id), None);
     }
 }
@@ -0,0 +1,85 @@
+//! Asynchronous access to the hotkey events through a `futures_core::Stream`. Only available with
+//! the `tokio` feature.
+//!
+//! ## Thread model
+//! The windows message loop is blocking and bound to the thread that registered the hotkeys, so
+//! it can't run on an async executor. `HotkeyManager::into_stream` moves the threadsafe
+//! `HotkeyManager`, which already handles the hotkeys on its own backend thread, to a dedicated
+//! thread that waits for hotkey events. The return values of the callbacks are sent to the
+//! `HotkeyStream` through a `tokio::sync::mpsc` channel. This is why the callback return type `T`
+//! needs to be `Send`.
+//!
+//! Dropping the `HotkeyStream` interrupts the waiting thread, which stops it and drops the
+//! `HotkeyManager`, unregistering all hotkeys.
+//!
+
+use std::{
+    pin::Pin,
+    task::{Context, Poll},
+    thread,
+};
+
+use futures_core::Stream;
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver};
+
+use crate::{error::HkError, threadsafe::HotkeyManager, HotkeyManagerImpl, InterruptHandle};
+
+/// Stream of the return values of the executed hotkey callbacks, created by
+/// `HotkeyManager::into_stream`. See the module documentation for the thread model.
+///
+/// The stream ends when the `HotkeyManager` is interrupted through an `InterruptHandle`, stopped
+/// through a `LoopQuitter` or when reading from the message queue fails.
+///
+pub struct HotkeyStream<T> {
+    receiver: UnboundedReceiver<T>,
+    interrupt: InterruptHandle,
+}
+
+impl<T: Send + 'static> HotkeyManager<T> {
+    /// Turn the `HotkeyManager` into a `HotkeyStream` that yields the return values of the
+    /// executed hotkey callbacks. The hotkeys need to be registered before, since the
+    /// `HotkeyManager` is moved to a dedicated thread.
+    ///
+    /// Hotkey events that don't lead to a callback being executed and panicking callbacks are
+    /// skipped.
+    ///
+    pub fn into_stream(self) -> HotkeyStream<T> {
+        let (snd, receiver) = unbounded_channel();
+        let interrupt = self.interrupt_handle();
+
+        thread::spawn(move || loop {
+            match self.handle_hotkey() {
+                Ok(Some(ret_val)) => {
+                    // The stream was dropped, so nobody is left to receive the hotkeys
+                    if snd.send(ret_val).is_err() {
+                        break;
+                    }
+                }
+                Err(HkError::CallbackPanicked(_)) => (),
+                Ok(None) | Err(_) => break,
+            }
+        });
+
+        HotkeyStream {
+            receiver,
+            interrupt,
+        }
+    }
+}
+
+impl<T> Stream for HotkeyStream<T> {
+    type Item = T;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        self.receiver.poll_recv(cx)
+    }
+}
+
+impl<T> Drop for HotkeyStream<T> {
+    fn drop(&mut self) {
+        // Stop the thread that waits for the hotkey events. This doesn't wait for the thread to
+        // finish, so dropping the stream never blocks the executor
+        self.receiver.close();
+        self.interrupt.interrupt();
+    }
+}
@@ -1,29 +1,67 @@
 use std::{
+    collections::HashMap,
     marker::PhantomData,
     sync::mpsc::{channel, Receiver, Sender},
     thread::{spawn, JoinHandle},
+    time::Duration,
 };
 
 use crate::{
+    config::BindingDescriptor,
     error::HkError,
-    keys::{ModKey, VKey},
-    singlethreaded, HotkeyId, HotkeyManagerImpl, InterruptHandle,
+    keys::{ModKey, Modifiers, VKey},
+    singlethreaded, HotkeyContext, HotkeyEvent, HotkeyId, HotkeyInfo, HotkeyManagerImpl,
+    InterruptHandle, LoopQuitter, RegisterOptions,
 };
 
 struct Hotkey<T: 'static> {
     key: VKey,
     key_modifiers: Vec<ModKey>,
     extra_keys: Vec<VKey>,
-    callback: Box<dyn Fn() -> T + Send + 'static>,
+    options: RegisterOptions,
+    callback: Box<dyn FnMut(&HotkeyContext) -> T + Send + 'static>,
 }
 
 enum HkMsg<T: 'static> {
     Register(Sender<Result<HotkeyId, HkError>>, Hotkey<T>),
-    HandleHotkey(Sender<Option<T>>),
+    HandleHotkey(Sender<Result<Option<HotkeyEvent<T>>, HkError>>),
+    HandleHotkeyTimeout(Sender<Result<Option<T>, HkError>>, Duration),
+    TryHandleHotkey(Sender<Option<T>>),
+    DrainHotkeys(Sender<Vec<T>>),
+    ReplaceCallback(
+        Sender<Result<(), HkError>>,
+        HotkeyId,
+        Box<dyn FnMut() -> T + Send + 'static>,
+    ),
+    SetFallback(Sender<()>, Box<dyn FnMut(HotkeyId) -> T + Send + 'static>),
+    Rebind(Sender<Result<(), HkError>>, HotkeyId, VKey, Vec<ModKey>),
+    RegisterReplacing(
+        Sender<Result<HotkeyId, HkError>>,
+        VKey,
+        Vec<ModKey>,
+        Box<dyn Fn() -> T + Send + 'static>,
+    ),
     Unregister(Sender<Result<(), HkError>>, HotkeyId),
+    Disable(Sender<Result<(), HkError>>, HotkeyId),
+    Enable(Sender<Result<(), HkError>>, HotkeyId),
+    Trigger(Sender<Result<(), HkError>>, HotkeyId),
+    PauseAll(Sender<Result<(), HkError>>),
+    ResumeAll(Sender<Result<(), HkError>>),
+    UnregisterCombo(Sender<Result<(), HkError>>, VKey, Vec<ModKey>),
     UnregisterAll(Sender<Result<(), HkError>>),
-    EventLoop(Sender<()>),
+    Clear(Sender<Result<(), HkError>>),
+    RegisteredIds(Sender<Vec<HotkeyId>>),
+    Registered(Sender<Vec<HotkeyInfo>>),
+    DumpConfig(Sender<Vec<BindingDescriptor>>),
+    IsRegistered(Sender<bool>, VKey, Vec<ModKey>),
+    CanRegister(Sender<Result<(), HkError>>, VKey, Vec<ModKey>),
+    Len(Sender<usize>),
+    FireCount(Sender<Option<u64>>, HotkeyId),
+    FireCounts(Sender<HashMap<HotkeyId, u64>>),
+    EventLoop(Sender<Result<(), HkError>>),
+    OwnerThreadId(Sender<Option<u32>>),
     InterruptHandle(Sender<InterruptHandle>),
+    QuitHandle(Sender<LoopQuitter>),
     Exit(Sender<()>),
 }
 
@@ -42,8 +80,13 @@ impl<T> TSHotkeyManagerBackend<T> {
     /// Create a new HotkeyManager instance. To work around the same-thread limitation of the
     /// windows event API, this will launch a new background thread to handle hotkey interactions.
     ///
-    fn new(rec: Receiver<HkMsg<T>>) -> Self {
-        let hkm = singlethreaded::HotkeyManager::new();
+    /// Without an `id_offset`, the ids are taken from the process-global id pool.
+    ///
+    fn new(rec: Receiver<HkMsg<T>>, id_offset: Option<i32>) -> Self {
+        let hkm = match id_offset {
+            Some(id_offset) => singlethreaded::HotkeyManager::new_with_id_offset(id_offset),
+            None => singlethreaded::HotkeyManager::new(),
+        };
         Self { hkm, rec }
     }
 
@@ -51,34 +94,131 @@ impl<T> TSHotkeyManagerBackend<T> {
         while let Ok(msg) = self.rec.recv() {
             match msg {
                 HkMsg::Register(chan_ret, hk) => {
-                    let ret_val = self.hkm.register_extrakeys(
+                    let ret_val = self.hkm.register_ctx_opts(
                         hk.key,
                         &hk.key_modifiers,
                         &hk.extra_keys,
+                        hk.options,
                         hk.callback,
                     );
                     chan_ret.send(ret_val).unwrap();
                 }
                 HkMsg::HandleHotkey(chan_ret) => {
-                    let ret_val = self.hkm.handle_hotkey();
+                    let ret_val = self.hkm.handle_hotkey_detailed();
+                    chan_ret.send(ret_val).unwrap();
+                }
+                HkMsg::HandleHotkeyTimeout(chan_ret, timeout) => {
+                    let ret_val = self.hkm.handle_hotkey_timeout(timeout);
+                    chan_ret.send(ret_val).unwrap();
+                }
+                HkMsg::TryHandleHotkey(chan_ret) => {
+                    let ret_val = self.hkm.try_handle_hotkey();
+                    chan_ret.send(ret_val).unwrap();
+                }
+                HkMsg::DrainHotkeys(chan_ret) => {
+                    let ret_val = self.hkm.drain_hotkeys();
+                    chan_ret.send(ret_val).unwrap();
+                }
+                HkMsg::ReplaceCallback(chan_ret, hkid, callback) => {
+                    let ret_val = self.hkm.replace_callback(hkid, callback);
+                    chan_ret.send(ret_val).unwrap();
+                }
+                HkMsg::SetFallback(chan_ret, callback) => {
+                    self.hkm.set_fallback(callback);
+                    chan_ret.send(()).unwrap();
+                }
+                HkMsg::Rebind(chan_ret, hkid, key, key_modifiers) => {
+                    let ret_val = self.hkm.rebind(hkid, key, &key_modifiers);
+                    chan_ret.send(ret_val).unwrap();
+                }
+                HkMsg::RegisterReplacing(chan_ret, key, key_modifiers, callback) => {
+                    let ret_val = self.hkm.register_replacing(key, &key_modifiers, callback);
                     chan_ret.send(ret_val).unwrap();
                 }
                 HkMsg::Unregister(chan_ret, hkid) => {
                     let ret_val = self.hkm.unregister(hkid);
                     chan_ret.send(ret_val).unwrap();
                 }
+                HkMsg::Disable(chan_ret, id) => {
+                    let ret_val = self.hkm.disable(id);
+                    chan_ret.send(ret_val).unwrap();
+                }
+                HkMsg::Enable(chan_ret, id) => {
+                    let ret_val = self.hkm.enable(id);
+                    chan_ret.send(ret_val).unwrap();
+                }
+                HkMsg::Trigger(chan_ret, id) => {
+                    let ret_val = self.hkm.trigger(id);
+                    chan_ret.send(ret_val).unwrap();
+                }
+                HkMsg::PauseAll(chan_ret) => {
+                    let ret_val = self.hkm.pause_all();
+                    chan_ret.send(ret_val).unwrap();
+                }
+                HkMsg::ResumeAll(chan_ret) => {
+                    let ret_val = self.hkm.resume_all();
+                    chan_ret.send(ret_val).unwrap();
+                }
+                HkMsg::UnregisterCombo(chan_ret, key, key_modifiers) => {
+                    let ret_val = self.hkm.unregister_combo(key, &key_modifiers);
+                    chan_ret.send(ret_val).unwrap();
+                }
                 HkMsg::UnregisterAll(chan_ret) => {
                     let ret_val = self.hkm.unregister_all();
                     chan_ret.send(ret_val).unwrap();
                 }
+                HkMsg::Clear(chan_ret) => {
+                    let ret_val = self.hkm.clear();
+                    chan_ret.send(ret_val).unwrap();
+                }
+                HkMsg::RegisteredIds(chan_ret) => {
+                    let ret_val = self.hkm.registered_ids();
+                    chan_ret.send(ret_val).unwrap();
+                }
+                HkMsg::Registered(chan_ret) => {
+                    let ret_val = self.hkm.registered();
+                    chan_ret.send(ret_val).unwrap();
+                }
+                HkMsg::DumpConfig(chan_ret) => {
+                    let ret_val = self.hkm.dump_config();
+                    chan_ret.send(ret_val).unwrap();
+                }
+                HkMsg::IsRegistered(chan_ret, key, key_modifiers) => {
+                    let ret_val = self.hkm.is_registered(key, &key_modifiers);
+                    chan_ret.send(ret_val).unwrap();
+                }
+                HkMsg::CanRegister(chan_ret, key, key_modifiers) => {
+                    let ret_val = self.hkm.can_register(key, &key_modifiers);
+                    chan_ret.send(ret_val).unwrap();
+                }
+                HkMsg::Len(chan_ret) => {
+                    let ret_val = self.hkm.len();
+                    chan_ret.send(ret_val).unwrap();
+                }
+                HkMsg::FireCount(chan_ret, id) => {
+                    let ret_val = self.hkm.fire_count(id);
+                    chan_ret.send(ret_val).unwrap();
+                }
+                HkMsg::FireCounts(chan_ret) => {
+                    let ret_val = self.hkm.fire_counts();
+                    chan_ret.send(ret_val).unwrap();
+                }
                 HkMsg::EventLoop(chan_ret) => {
-                    self.hkm.event_loop();
-                    chan_ret.send(()).unwrap();
+                    let ret_val = self.hkm.event_loop();
+                    chan_ret.send(ret_val).unwrap();
+                }
+                HkMsg::OwnerThreadId(chan_ret) => {
+                    let ret_val = self.hkm.owner_thread_id();
+                    chan_ret.send(ret_val).unwrap();
                 }
                 HkMsg::InterruptHandle(chan_ret) => {
                     let ret_val = self.hkm.interrupt_handle();
                     chan_ret.send(ret_val).unwrap();
                 }
+                HkMsg::QuitHandle(chan_ret) => {
+                    let ret_val = self.hkm.quit_handle();
+                    chan_ret.send(ret_val).unwrap();
+                }
                 HkMsg::Exit(chan_ret) => {
                     chan_ret.send(()).unwrap();
                     return;
@@ -88,25 +228,44 @@ impl<T> TSHotkeyManagerBackend<T> {
     }
 }
 
-impl<T: 'static + Send> HotkeyManagerImpl<T> for HotkeyManager<T> {
-    fn new() -> Self {
+impl<T: 'static + Send> HotkeyManager<T> {
+    /// Launch the background thread with a singlethreaded `HotkeyManager` that handles the hotkey
+    /// interactions.
+    ///
+    fn spawn(id_offset: Option<i32>) -> Self {
         let (snd, rec) = channel();
         let backend_handle = spawn(move || {
-            let mut backend = TSHotkeyManagerBackend::<T>::new(rec);
+            let mut backend = TSHotkeyManagerBackend::<T>::new(rec, id_offset);
             backend.backend_loop();
         });
 
         Self {
-            _phantom: PhantomData::default(),
+            _phantom: PhantomData,
             snd,
             backend_handle: Some(backend_handle),
         }
     }
+}
+
+impl<T: 'static + Send> Default for HotkeyManager<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: 'static + Send> HotkeyManagerImpl<T> for HotkeyManager<T> {
+    fn new() -> Self {
+        Self::spawn(None)
+    }
+
+    fn new_with_id_offset(id_offset: i32) -> Self {
+        Self::spawn(Some(id_offset))
+    }
 
     fn register(
         &mut self,
         key: VKey,
-        key_modifiers: &[ModKey],
+        key_modifiers: impl Into<Modifiers>,
         callback: impl Fn() -> T + Send + 'static,
     ) -> Result<HotkeyId, HkError> {
         self.register_extrakeys(key, key_modifiers, &[], callback)
@@ -115,50 +274,260 @@ impl<T: 'static + Send> HotkeyManagerImpl<T> for HotkeyManager<T> {
     fn register_extrakeys(
         &mut self,
         key: VKey,
-        key_modifiers: &[ModKey],
+        key_modifiers: impl Into<Modifiers>,
         extra_keys: &[VKey],
         callback: impl Fn() -> T + Send + 'static,
+    ) -> Result<HotkeyId, HkError> {
+        self.register_extrakeys_opts(
+            key,
+            &key_modifiers.into().to_vec(),
+            extra_keys,
+            RegisterOptions::default(),
+            callback,
+        )
+    }
+
+    fn register_ctx_opts(
+        &mut self,
+        key: VKey,
+        key_modifiers: &[ModKey],
+        extra_keys: &[VKey],
+        options: RegisterOptions,
+        callback: impl FnMut(&HotkeyContext) -> T + Send + 'static,
     ) -> Result<HotkeyId, HkError> {
         let ret_ch = channel();
         let hk = Hotkey {
             key,
             key_modifiers: key_modifiers.to_vec(),
             extra_keys: extra_keys.to_vec(),
+            options,
             callback: Box::new(callback),
         };
         self.snd.send(HkMsg::Register(ret_ch.0, hk)).unwrap();
         ret_ch.1.recv().unwrap()
     }
 
+    fn replace_callback(
+        &mut self,
+        id: HotkeyId,
+        callback: impl FnMut() -> T + Send + 'static,
+    ) -> Result<(), HkError> {
+        let ret_ch = channel();
+        self.snd
+            .send(HkMsg::ReplaceCallback(ret_ch.0, id, Box::new(callback)))
+            .unwrap();
+        ret_ch.1.recv().unwrap()
+    }
+
+    fn set_fallback(&mut self, callback: impl FnMut(HotkeyId) -> T + Send + 'static) {
+        let ret_ch = channel();
+        self.snd
+            .send(HkMsg::SetFallback(ret_ch.0, Box::new(callback)))
+            .unwrap();
+        ret_ch.1.recv().unwrap()
+    }
+
+    fn rebind(
+        &mut self,
+        id: HotkeyId,
+        key: VKey,
+        key_modifiers: impl Into<Modifiers>,
+    ) -> Result<(), HkError> {
+        let ret_ch = channel();
+        self.snd
+            .send(HkMsg::Rebind(
+                ret_ch.0,
+                id,
+                key,
+                key_modifiers.into().to_vec(),
+            ))
+            .unwrap();
+        ret_ch.1.recv().unwrap()
+    }
+
+    fn register_replacing(
+        &mut self,
+        key: VKey,
+        key_modifiers: &[ModKey],
+        callback: impl Fn() -> T + Send + 'static,
+    ) -> Result<HotkeyId, HkError> {
+        let ret_ch = channel();
+        self.snd
+            .send(HkMsg::RegisterReplacing(
+                ret_ch.0,
+                key,
+                key_modifiers.to_vec(),
+                Box::new(callback),
+            ))
+            .unwrap();
+        ret_ch.1.recv().unwrap()
+    }
+
     fn unregister(&mut self, id: HotkeyId) -> Result<(), HkError> {
         let ret_ch = channel();
         self.snd.send(HkMsg::Unregister(ret_ch.0, id)).unwrap();
         ret_ch.1.recv().unwrap()
     }
 
+    fn disable(&mut self, id: HotkeyId) -> Result<(), HkError> {
+        let ret_ch = channel();
+        self.snd.send(HkMsg::Disable(ret_ch.0, id)).unwrap();
+        ret_ch.1.recv().unwrap()
+    }
+
+    fn enable(&mut self, id: HotkeyId) -> Result<(), HkError> {
+        let ret_ch = channel();
+        self.snd.send(HkMsg::Enable(ret_ch.0, id)).unwrap();
+        ret_ch.1.recv().unwrap()
+    }
+
+    fn trigger(&self, id: HotkeyId) -> Result<(), HkError> {
+        let ret_ch = channel();
+        self.snd.send(HkMsg::Trigger(ret_ch.0, id)).unwrap();
+        ret_ch.1.recv().unwrap()
+    }
+
+    fn pause_all(&mut self) -> Result<(), HkError> {
+        let ret_ch = channel();
+        self.snd.send(HkMsg::PauseAll(ret_ch.0)).unwrap();
+        ret_ch.1.recv().unwrap()
+    }
+
+    fn resume_all(&mut self) -> Result<(), HkError> {
+        let ret_ch = channel();
+        self.snd.send(HkMsg::ResumeAll(ret_ch.0)).unwrap();
+        ret_ch.1.recv().unwrap()
+    }
+
+    fn unregister_combo(&mut self, key: VKey, key_modifiers: &[ModKey]) -> Result<(), HkError> {
+        let ret_ch = channel();
+        self.snd
+            .send(HkMsg::UnregisterCombo(
+                ret_ch.0,
+                key,
+                key_modifiers.to_vec(),
+            ))
+            .unwrap();
+        ret_ch.1.recv().unwrap()
+    }
+
     fn unregister_all(&mut self) -> Result<(), HkError> {
         let ret_ch = channel();
         self.snd.send(HkMsg::UnregisterAll(ret_ch.0)).unwrap();
         ret_ch.1.recv().unwrap()
     }
 
-    fn handle_hotkey(&self) -> Option<T> {
+    fn clear(&mut self) -> Result<(), HkError> {
+        let ret_ch = channel();
+        self.snd.send(HkMsg::Clear(ret_ch.0)).unwrap();
+        ret_ch.1.recv().unwrap()
+    }
+
+    fn registered_ids(&self) -> Vec<HotkeyId> {
+        let ret_ch = channel();
+        self.snd.send(HkMsg::RegisteredIds(ret_ch.0)).unwrap();
+        ret_ch.1.recv().unwrap()
+    }
+
+    fn registered(&self) -> Vec<HotkeyInfo> {
+        let ret_ch = channel();
+        self.snd.send(HkMsg::Registered(ret_ch.0)).unwrap();
+        ret_ch.1.recv().unwrap()
+    }
+
+    fn dump_config(&self) -> Vec<BindingDescriptor> {
+        let ret_ch = channel();
+        self.snd.send(HkMsg::DumpConfig(ret_ch.0)).unwrap();
+        ret_ch.1.recv().unwrap()
+    }
+
+    fn is_registered(&self, key: VKey, key_modifiers: &[ModKey]) -> bool {
+        let ret_ch = channel();
+        self.snd
+            .send(HkMsg::IsRegistered(ret_ch.0, key, key_modifiers.to_vec()))
+            .unwrap();
+        ret_ch.1.recv().unwrap()
+    }
+
+    fn can_register(&self, key: VKey, key_modifiers: &[ModKey]) -> Result<(), HkError> {
+        let ret_ch = channel();
+        self.snd
+            .send(HkMsg::CanRegister(ret_ch.0, key, key_modifiers.to_vec()))
+            .unwrap();
+        ret_ch.1.recv().unwrap()
+    }
+
+    fn len(&self) -> usize {
+        let ret_ch = channel();
+        self.snd.send(HkMsg::Len(ret_ch.0)).unwrap();
+        ret_ch.1.recv().unwrap()
+    }
+
+    fn fire_count(&self, id: HotkeyId) -> Option<u64> {
+        let ret_ch = channel();
+        self.snd.send(HkMsg::FireCount(ret_ch.0, id)).unwrap();
+        ret_ch.1.recv().unwrap()
+    }
+
+    fn fire_counts(&self) -> HashMap<HotkeyId, u64> {
+        let ret_ch = channel();
+        self.snd.send(HkMsg::FireCounts(ret_ch.0)).unwrap();
+        ret_ch.1.recv().unwrap()
+    }
+
+    fn handle_hotkey(&self) -> Result<Option<T>, HkError> {
+        Ok(self.handle_hotkey_with_id()?.map(|(_, ret)| ret))
+    }
+
+    fn handle_hotkey_detailed(&self) -> Result<Option<HotkeyEvent<T>>, HkError> {
         let ret_ch = channel();
         self.snd.send(HkMsg::HandleHotkey(ret_ch.0)).unwrap();
         ret_ch.1.recv().unwrap()
     }
 
-    fn event_loop(&self) {
+    fn handle_hotkey_timeout(&self, timeout: Duration) -> Result<Option<T>, HkError> {
+        let ret_ch = channel();
+        self.snd
+            .send(HkMsg::HandleHotkeyTimeout(ret_ch.0, timeout))
+            .unwrap();
+        ret_ch.1.recv().unwrap()
+    }
+
+    fn try_handle_hotkey(&self) -> Option<T> {
+        let ret_ch = channel();
+        self.snd.send(HkMsg::TryHandleHotkey(ret_ch.0)).unwrap();
+        ret_ch.1.recv().unwrap()
+    }
+
+    fn drain_hotkeys(&self) -> Vec<T> {
+        let ret_ch = channel();
+        self.snd.send(HkMsg::DrainHotkeys(ret_ch.0)).unwrap();
+        ret_ch.1.recv().unwrap()
+    }
+
+    fn event_loop(&self) -> Result<(), HkError> {
         let ret_ch = channel();
         self.snd.send(HkMsg::EventLoop(ret_ch.0)).unwrap();
         ret_ch.1.recv().unwrap()
     }
 
+    fn owner_thread_id(&self) -> Option<u32> {
+        let ret_ch = channel();
+        self.snd.send(HkMsg::OwnerThreadId(ret_ch.0)).unwrap();
+        ret_ch.1.recv().unwrap()
+    }
+
     fn interrupt_handle(&self) -> InterruptHandle {
         let ret_ch = channel();
         self.snd.send(HkMsg::InterruptHandle(ret_ch.0)).unwrap();
         ret_ch.1.recv().unwrap()
     }
+
+    fn quit_handle(&self) -> LoopQuitter {
+        let ret_ch = channel();
+        self.snd.send(HkMsg::QuitHandle(ret_ch.0)).unwrap();
+        ret_ch.1.recv().unwrap()
+    }
 }
 
 impl<T> Drop for HotkeyManager<T> {
@@ -169,3 +538,11 @@ impl<T> Drop for HotkeyManager<T> {
         self.backend_handle.take().unwrap().join().unwrap();
     }
 }
+
+// The threadsafe `HotkeyManager` is only a stub for the backend thread, so it must stay
+// thread-safe as well
+const _: () = {
+    const fn assert_send_sync<S: Send + Sync>() {}
+
+    assert_send_sync::<HotkeyManager<()>>();
+};